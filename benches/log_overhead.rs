@@ -0,0 +1,65 @@
+//! Per-message cost of the planet logging, with every event enabled and with
+//! everything filtered out by the verbosity policy, for lazy payloads and for
+//! the eager baseline that builds every payload before filtering it.
+//!
+//! Usage: `cargo bench --bench log_overhead -- [messages]`
+
+use common_game::components::resource::BasicResourceType;
+use common_game::protocols::planet_explorer::ExplorerToPlanet;
+use std::hint::black_box;
+use std::time::Instant;
+use Planet::{CallbackSink, LogVerbosity, PlanetConfig, RocketStrategy, SimulatedPlanet};
+
+fn run(verbosity: LogVerbosity, messages: u32) -> Result<f64, String> {
+    // The sink only has to consume the event, so the numbers measure building it.
+    let config = PlanetConfig::new(1, RocketStrategy::Safe, Some(BasicResourceType::Hydrogen))
+        .with_log_sink(CallbackSink::new(|event| {
+            black_box(event);
+        }))
+        .with_verbosity(verbosity);
+    let mut planet = SimulatedPlanet::new(config)?;
+
+    let start = Instant::now();
+    for i in 0..messages {
+        let explorer_id = i % 4;
+        match i % 6 {
+            0 | 1 => planet.sunray(),
+            2 => {
+                black_box(planet.internal_state());
+            }
+            3 => {
+                black_box(planet.explorer_msg(ExplorerToPlanet::SupportedResourceRequest { explorer_id }));
+            }
+            4 => {
+                black_box(planet.explorer_msg(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id }));
+            }
+            _ => {
+                black_box(planet.explorer_msg(ExplorerToPlanet::GenerateResourceRequest {
+                    explorer_id,
+                    resource: BasicResourceType::Hydrogen,
+                }));
+            }
+        }
+    }
+    Ok(start.elapsed().as_nanos() as f64 / f64::from(messages))
+}
+
+fn main() -> Result<(), String> {
+    // `cargo bench` passes `--bench`; anything numeric is the message count.
+    let messages = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse().ok())
+        .unwrap_or(200_000);
+    if messages == 0 {
+        return Err("the message count must be at least 1".to_string());
+    }
+
+    println!("messages: {messages}");
+    println!("{:<20} {:>15} {:>15}", "", "eager", "lazy");
+    for (name, verbosity) in [("all events logged", LogVerbosity::default()), ("all events filtered", LogVerbosity::off())] {
+        let eager = run(verbosity.clone().with_eager_payloads(), messages)?;
+        let lazy = run(verbosity, messages)?;
+        println!("{name:<20} {eager:>8.1} ns/msg {lazy:>8.1} ns/msg");
+    }
+    Ok(())
+}
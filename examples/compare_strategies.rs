@@ -0,0 +1,27 @@
+//! Runs the strategy comparison and prints it as a text table.
+//!
+//! Usage: `cargo run --release --example compare_strategies -- [games] [--csv]`
+
+use Planet::{evaluate_strategies, EvaluationParams};
+
+fn main() -> Result<(), String> {
+    let mut params = EvaluationParams::default();
+    let mut csv = false;
+    for arg in std::env::args().skip(1) {
+        if arg == "--csv" {
+            csv = true;
+        } else {
+            params.games = arg
+                .parse()
+                .map_err(|_| format!("invalid number of games: {arg}"))?;
+        }
+    }
+
+    let table = evaluate_strategies(&params)?;
+    if csv {
+        print!("{}", table.to_csv());
+    } else {
+        print!("{table}");
+    }
+    Ok(())
+}
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Run with `cargo fuzz run explorer_msg` from the repository root.
+fuzz_target!(|data: &[u8]| {
+    Planet::fuzzing::drive_explorer_messages(data);
+});
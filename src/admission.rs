@@ -0,0 +1,123 @@
+use crate::sessions::ExplorerSession;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+/// What the planet knows when an explorer asks to land.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Landing {
+    pub explorer_id: u32,
+    /// Explorers already landed and accepted.
+    pub explorers_on_planet: usize,
+    pub charged_cells: u32,
+    pub total_cells: u32,
+}
+
+/// Decides which explorers the planet serves, see
+/// [`PlanetConfig::with_explorer_policy`](crate::PlanetConfig::with_explorer_policy).
+///
+/// The `common_game` runtime lets every explorer land and answers
+/// `IncomingExplorerResponse { res: Ok(()) }` itself, so on the raw channels
+/// of [`create_planet`](crate::create_planet) a rejected explorer looks
+/// accepted: it is on the planet until it leaves, and gets no answer to any
+/// of its requests. Every decision is logged as an `ExplorerAdmission` event
+/// sent to the orchestrator, with the reason of a rejection.
+///
+/// [`PlanetHandle::incoming_explorer`](crate::PlanetHandle::incoming_explorer),
+/// and so [`ExplorerClient::connect`](crate::ExplorerClient::connect), is the
+/// supported way to learn the decision: it sends a rejected explorer away
+/// again and fails with [`PlanetError::Rejected`](crate::PlanetError::Rejected)
+/// and the reason.
+pub trait ExplorerPolicy: Send + Sync + Debug {
+    /// `Err` with the reason to reject the explorer.
+    fn admit(&self, landing: &Landing) -> Result<(), String>;
+
+    /// Called when an explorer leaves; `session` is `None` if it was rejected.
+    fn on_departure(&self, _explorer_id: u32, _session: Option<&ExplorerSession>) {}
+}
+
+/// The built-in [`ExplorerPolicy`]; the default accepts every explorer.
+///
+/// - `max_explorers`: most explorers served at once, unlimited if `None`.
+/// - `blacklist`: explorers never served.
+/// - `min_charged_cells`: charged cells the planet needs to accept a newcomer.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AdmissionPolicy {
+    pub max_explorers: Option<usize>,
+    pub blacklist: HashSet<u32>,
+    pub min_charged_cells: u32,
+}
+
+impl ExplorerPolicy for AdmissionPolicy {
+    fn admit(&self, landing: &Landing) -> Result<(), String> {
+        if self.blacklist.contains(&landing.explorer_id) {
+            return Err("blacklisted".to_string());
+        }
+        if let Some(max) = self.max_explorers
+            && landing.explorers_on_planet >= max
+        {
+            return Err(format!("{} explorers already on the planet", landing.explorers_on_planet));
+        }
+        if landing.charged_cells < self.min_charged_cells {
+            return Err(format!(
+                "{} charged cells, {} needed",
+                landing.charged_cells, self.min_charged_cells
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Explorers on the planet that the policy rejected, with the reason.
+///
+/// Shared by the AI with the [`PlanetHandle`](crate::PlanetHandle), which can
+/// only see the `Ok` the runtime answers every landing with.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Rejections(Arc<Mutex<HashMap<u32, String>>>);
+
+impl Rejections {
+    pub(crate) fn insert(&self, explorer_id: u32, reason: String) {
+        self.lock().insert(explorer_id, reason);
+    }
+
+    pub(crate) fn remove(&self, explorer_id: u32) {
+        self.lock().remove(&explorer_id);
+    }
+
+    pub(crate) fn contains(&self, explorer_id: u32) -> bool {
+        self.lock().contains_key(&explorer_id)
+    }
+
+    pub(crate) fn reason(&self, explorer_id: u32) -> Option<String> {
+        self.lock().get(&explorer_id).cloned()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u32, String>> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn landing(explorer_id: u32, explorers_on_planet: usize, charged_cells: u32) -> Landing {
+        Landing { explorer_id, explorers_on_planet, charged_cells, total_cells: 5 }
+    }
+
+    #[test]
+    fn test_admission_policy() {
+        assert!(AdmissionPolicy::default().admit(&landing(1, 100, 0)).is_ok());
+
+        let policy = AdmissionPolicy {
+            max_explorers: Some(2),
+            blacklist: HashSet::from([13]),
+            min_charged_cells: 1,
+        };
+        assert!(policy.admit(&landing(7, 1, 1)).is_ok());
+        assert_eq!(policy.admit(&landing(13, 0, 5)).unwrap_err(), "blacklisted");
+        assert_eq!(policy.admit(&landing(7, 2, 5)).unwrap_err(), "2 explorers already on the planet");
+        assert_eq!(policy.admit(&landing(7, 0, 0)).unwrap_err(), "0 charged cells, 1 needed");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::cells::PlanetCells;
+use common_game::components::energy_cell::EnergyCell;
+
+/// Which charged cell is spent when the planet builds a rocket or generates a
+/// resource for an explorer.
+///
+/// The index of the cell spent is logged: `rocketCells` in the `SunrayAck` and
+/// `AsteroidAck` events, `cellIndex` in successful `GenerateResourceResponse`s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum CellSelection {
+    /// The charged cell with the lowest index.
+    #[default]
+    FirstFull,
+    /// The charged cell with the highest index.
+    LastFull,
+    /// The next charged cell after the one spent last, wrapping around.
+    RoundRobin,
+    /// The `cells` lowest-index cells are dedicated to rockets: explorers are
+    /// only served from the others. Rockets use the lowest charged cell,
+    /// dedicated or not.
+    LowestForRockets { cells: usize },
+}
+
+/// What a cell is spent on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Purpose {
+    Rocket,
+    Trade,
+}
+
+/// A [`CellSelection`] with the position of the round robin.
+#[derive(Debug, Default)]
+pub(crate) struct CellSelector {
+    policy: CellSelection,
+    next: usize,
+}
+
+impl CellSelector {
+    pub(crate) fn new(policy: CellSelection) -> Self {
+        CellSelector { policy, next: 0 }
+    }
+
+    /// The charged cell to spend on `purpose`, if any.
+    pub(crate) fn pick<S: PlanetCells>(&mut self, state: &S, purpose: Purpose) -> Option<usize> {
+        let cells = state.cells_iter().as_slice();
+        match self.policy {
+            CellSelection::FirstFull => cells.iter().position(EnergyCell::is_charged),
+            CellSelection::LastFull => cells.iter().rposition(EnergyCell::is_charged),
+            CellSelection::RoundRobin => {
+                let i = (0..cells.len())
+                    .map(|k| (self.next + k) % cells.len())
+                    .find(|i| cells[*i].is_charged())?;
+                self.next = i + 1;
+                Some(i)
+            }
+            CellSelection::LowestForRockets { cells: dedicated } => match purpose {
+                Purpose::Rocket => cells.iter().position(EnergyCell::is_charged),
+                Purpose::Trade => cells
+                    .iter()
+                    .enumerate()
+                    .skip(dedicated)
+                    .find(|(_, c)| c.is_charged())
+                    .map(|(i, _)| i),
+            },
+        }
+    }
+
+    /// Charged cells explorers can't be served from.
+    pub(crate) fn closed_to_trade<S: PlanetCells>(&self, state: &S) -> u32 {
+        match self.policy {
+            CellSelection::LowestForRockets { cells } => {
+                state.cells_iter().take(cells).filter(|c| c.is_charged()).count() as u32
+            }
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimulatedPlanetState;
+    use common_game::components::planet::PlanetType;
+    use common_game::components::sunray::Sunray;
+
+    /// A type A state with the given cells charged.
+    fn state(charged: &[usize]) -> SimulatedPlanetState {
+        let mut state = SimulatedPlanetState::new(1, PlanetType::A);
+        for i in charged {
+            state.cell_mut(*i).charge(Sunray::default());
+        }
+        state
+    }
+
+    fn picks(policy: CellSelection, state: &SimulatedPlanetState, purpose: Purpose, n: usize) -> Vec<Option<usize>> {
+        let mut selector = CellSelector::new(policy);
+        (0..n).map(|_| selector.pick(state, purpose)).collect()
+    }
+
+    #[test]
+    fn test_policies() {
+        let state = state(&[1, 2, 4]);
+        assert_eq!(picks(CellSelection::FirstFull, &state, Purpose::Trade, 2), [Some(1), Some(1)]);
+        assert_eq!(picks(CellSelection::LastFull, &state, Purpose::Trade, 2), [Some(4), Some(4)]);
+        assert_eq!(
+            picks(CellSelection::RoundRobin, &state, Purpose::Trade, 4),
+            [Some(1), Some(2), Some(4), Some(1)]
+        );
+        assert_eq!(picks(CellSelection::RoundRobin, &self::state(&[]), Purpose::Rocket, 1), [None]);
+    }
+
+    #[test]
+    fn test_lowest_cells_for_rockets() {
+        let policy = CellSelection::LowestForRockets { cells: 2 };
+        let selector = CellSelector::new(policy);
+
+        let state = state(&[1, 3]);
+        assert_eq!(picks(policy, &state, Purpose::Rocket, 1), [Some(1)]);
+        assert_eq!(picks(policy, &state, Purpose::Trade, 1), [Some(3)]);
+        assert_eq!(selector.closed_to_trade(&state), 1);
+
+        let state = self::state(&[0, 1]);
+        assert_eq!(picks(policy, &state, Purpose::Trade, 1), [None]);
+        assert_eq!(selector.closed_to_trade(&state), 2);
+    }
+}
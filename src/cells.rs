@@ -0,0 +1,78 @@
+use common_game::components::energy_cell::EnergyCell;
+use common_game::components::planet::{DummyPlanetState, PlanetState};
+use common_game::components::sunray::Sunray;
+use std::slice::Iter;
+
+/// The part of a planet state the AI works with: energy cells and the rocket.
+///
+/// It is implemented by the runtime [`PlanetState`], which can only be built by
+/// `Planet::new`, and by [`SimulatedPlanetState`](crate::SimulatedPlanetState),
+/// which lets the AI run outside the planet thread.
+pub trait PlanetCells {
+    type Rocket;
+
+    fn id(&self) -> u32;
+    fn cells_iter(&self) -> Iter<'_, EnergyCell>;
+    fn cell_mut(&mut self, i: usize) -> &mut EnergyCell;
+    fn can_have_rocket(&self) -> bool;
+    fn has_rocket(&self) -> bool;
+    fn take_rocket(&mut self) -> Option<Self::Rocket>;
+    fn build_rocket(&mut self, i: usize) -> Result<(), String>;
+
+    /// Charges the first empty cell, giving the sunray back if there is none.
+    fn charge_cell(&mut self, sunray: Sunray) -> Option<Sunray> {
+        let Some((cell, _)) = self.empty_cell() else {
+            return Some(sunray);
+        };
+        cell.charge(sunray);
+        None
+    }
+
+    fn empty_cell(&mut self) -> Option<(&mut EnergyCell, usize)> {
+        let i = self.cells_iter().position(|c| !c.is_charged())?;
+        Some((self.cell_mut(i), i))
+    }
+
+    fn full_cell(&mut self) -> Option<(&mut EnergyCell, usize)> {
+        let i = self.cells_iter().position(EnergyCell::is_charged)?;
+        Some((self.cell_mut(i), i))
+    }
+
+    fn to_dummy(&self) -> DummyPlanetState {
+        let energy_cells: Vec<bool> = self.cells_iter().map(EnergyCell::is_charged).collect();
+        DummyPlanetState {
+            charged_cells_count: energy_cells.iter().filter(|c| **c).count(),
+            energy_cells,
+            has_rocket: self.has_rocket(),
+        }
+    }
+}
+
+impl PlanetCells for PlanetState {
+    type Rocket = common_game::components::rocket::Rocket;
+
+    fn id(&self) -> u32 {
+        PlanetState::id(self)
+    }
+    fn cells_iter(&self) -> Iter<'_, EnergyCell> {
+        PlanetState::cells_iter(self)
+    }
+    fn cell_mut(&mut self, i: usize) -> &mut EnergyCell {
+        PlanetState::cell_mut(self, i)
+    }
+    fn can_have_rocket(&self) -> bool {
+        PlanetState::can_have_rocket(self)
+    }
+    fn has_rocket(&self) -> bool {
+        PlanetState::has_rocket(self)
+    }
+    fn take_rocket(&mut self) -> Option<Self::Rocket> {
+        PlanetState::take_rocket(self)
+    }
+    fn build_rocket(&mut self, i: usize) -> Result<(), String> {
+        PlanetState::build_rocket(self, i)
+    }
+    fn to_dummy(&self) -> DummyPlanetState {
+        PlanetState::to_dummy(self)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::admission::ExplorerPolicy;
+use crate::cell_selection::CellSelection;
+use crate::forecast::EnergyBudget;
+use crate::planner::PlannerWeights;
+use crate::report::SessionCounters;
+use crate::reservation::ReservationPolicy;
+use crate::sink::LogSink;
+use crate::tiers::ServiceTiers;
+use crate::verbosity::LogVerbosity;
+use crate::RocketStrategy;
+use common_game::components::planet::PlanetType;
+use common_game::components::resource::{BasicResourceType, ComplexResourceType};
+use std::sync::Arc;
+
+/// Construction parameters of a planet.
+///
+/// `houston_we_have_a_borrow` covers the common case; use this struct when the
+/// planet needs options that are not part of that signature.
+///
+/// - `planet_id`: the id of the planet
+/// - `planet_type`: the rules the planet is built with, `A` by default
+/// - `rocket_strategy`: see [`RocketStrategy`]
+/// - `stockpile_cap`: most rockets kept by [`RocketStrategy::Stockpile`], at
+///   least 1 (the rocket on the planet), 3 by default
+/// - `planner`: the weights of [`RocketStrategy::Planner`]
+/// - `cell_selection`: which charged cell is spent, the first one by default
+/// - `basic_resource`: the resource the planet generates, `Hydrogen` if `None`
+/// - `combination_rules`: the complex resources the planet advertises, none by default
+/// - `reservation`: enables explorer cell reservations, disabled if `None`
+/// - `service_tiers`: enables energy-aware service tiers, disabled if `None`
+/// - `energy_budget`: limits the cells sold per period, disabled if `None`
+/// - `explorer_policy`: which landing explorers are served, all of them if `None`
+/// - `log_sink`: where the planet events go, the global `log` logger if `None`
+/// - `verbosity`: which events are logged at all, everything by default
+/// - `session_counters`: whether a restart resets the session counters
+///
+/// With the `serde` feature it can also be loaded from a TOML or JSON file,
+/// see `PlanetConfig::load`.
+#[derive(Debug, Clone)]
+pub struct PlanetConfig {
+    pub planet_id: u32,
+    pub planet_type: PlanetType,
+    pub rocket_strategy: RocketStrategy,
+    pub stockpile_cap: u32,
+    pub planner: PlannerWeights,
+    pub cell_selection: CellSelection,
+    pub basic_resource: Option<BasicResourceType>,
+    pub combination_rules: Vec<ComplexResourceType>,
+    pub reservation: Option<ReservationPolicy>,
+    pub service_tiers: Option<ServiceTiers>,
+    pub energy_budget: Option<EnergyBudget>,
+    pub explorer_policy: Option<Arc<dyn ExplorerPolicy>>,
+    pub log_sink: Option<Arc<dyn LogSink>>,
+    pub verbosity: LogVerbosity,
+    pub session_counters: SessionCounters,
+}
+
+impl PlanetConfig {
+    pub fn new(
+        planet_id: u32,
+        rocket_strategy: RocketStrategy,
+        basic_resource: Option<BasicResourceType>,
+    ) -> Self {
+        PlanetConfig {
+            planet_id,
+            planet_type: PlanetType::A,
+            rocket_strategy,
+            stockpile_cap: 3,
+            planner: PlannerWeights::default(),
+            cell_selection: CellSelection::default(),
+            basic_resource,
+            combination_rules: Vec::new(),
+            reservation: None,
+            service_tiers: None,
+            energy_budget: None,
+            explorer_policy: None,
+            log_sink: None,
+            verbosity: LogVerbosity::default(),
+            session_counters: SessionCounters::default(),
+        }
+    }
+
+    pub fn with_planet_type(mut self, planet_type: PlanetType) -> Self {
+        self.planet_type = planet_type;
+        self
+    }
+
+    pub fn with_combination_rules(mut self, rules: impl IntoIterator<Item = ComplexResourceType>) -> Self {
+        self.combination_rules = rules.into_iter().collect();
+        self
+    }
+
+    /// # Panics
+    /// If `cap` is 0: the Stockpile strategy always keeps the rocket on the
+    /// planet.
+    pub fn with_stockpile_cap(mut self, cap: u32) -> Self {
+        assert!(cap >= 1, "the stockpile cap must be at least 1");
+        self.stockpile_cap = cap;
+        self
+    }
+
+    pub fn with_planner_weights(mut self, weights: PlannerWeights) -> Self {
+        self.planner = weights;
+        self
+    }
+
+    pub fn with_cell_selection(mut self, selection: CellSelection) -> Self {
+        self.cell_selection = selection;
+        self
+    }
+
+    pub fn with_reservation(mut self, policy: ReservationPolicy) -> Self {
+        self.reservation = Some(policy);
+        self
+    }
+
+    pub fn with_service_tiers(mut self, tiers: ServiceTiers) -> Self {
+        self.service_tiers = Some(tiers);
+        self
+    }
+
+    pub fn with_energy_budget(mut self, budget: EnergyBudget) -> Self {
+        self.energy_budget = Some(budget);
+        self
+    }
+
+    pub fn with_explorer_policy(mut self, policy: impl ExplorerPolicy + 'static) -> Self {
+        self.explorer_policy = Some(Arc::new(policy));
+        self
+    }
+
+    pub fn with_log_sink(mut self, sink: impl LogSink + 'static) -> Self {
+        self.log_sink = Some(Arc::new(sink));
+        self
+    }
+
+    pub fn with_verbosity(mut self, verbosity: LogVerbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    pub fn with_session_counters(mut self, session_counters: SessionCounters) -> Self {
+        self.session_counters = session_counters;
+        self
+    }
+}
@@ -0,0 +1,825 @@
+use crate::admission::AdmissionPolicy;
+use crate::cell_selection::CellSelection;
+use crate::forecast::EnergyBudget;
+use crate::planner::PlannerWeights;
+use crate::report::SessionCounters;
+use crate::reservation::ReservationPolicy;
+use crate::sink::{GlobalLogger, JsonLinesFile, LogSink, StdoutSink};
+use crate::tiers::ServiceTiers;
+use crate::verbosity::LogVerbosity;
+use crate::{PlanetConfig, RocketStrategy};
+use common_game::components::planet::PlanetType;
+use common_game::components::resource::{BasicResourceType, ComplexResourceType};
+use common_game::logging::{Channel, EventType};
+use serde::de::{self, Deserialize, Deserializer};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Why a planet configuration file could not be loaded.
+///
+/// [`ConfigError::Invalid`] names the offending field by its dotted path,
+/// e.g. `reservation.max_cells` or `combination_rules[2]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io { path: PathBuf, reason: String },
+    /// The file extension is neither `.toml` nor `.json`.
+    UnknownFormat { path: PathBuf },
+    /// The file is not valid TOML or JSON.
+    Syntax { reason: String },
+    /// A field is missing, unknown, of the wrong type or out of range.
+    Invalid { field: String, reason: String },
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io { path, reason } => write!(f, "cannot read {}: {reason}", path.display()),
+            ConfigError::UnknownFormat { path } => {
+                write!(f, "{}: expected a .toml or .json file", path.display())
+            }
+            ConfigError::Syntax { reason } => write!(f, "invalid configuration: {reason}"),
+            ConfigError::Invalid { field, reason } => write!(f, "{field}: {reason}"),
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+impl PlanetConfig {
+    /// Loads a configuration from a `.toml` or `.json` file.
+    ///
+    /// ```toml
+    /// planet_id = 3
+    /// planet_type = "A"                 # A, B, C or D
+    /// basic_resource = "Oxygen"         # Hydrogen if missing
+    /// combination_rules = []
+    /// rocket_strategy = "Safe"          # Default if missing
+    /// stockpile_cap = 3                 # rockets kept by the Stockpile strategy, >= 1
+    /// cell_selection = "RoundRobin"     # FirstFull (default), LastFull, RoundRobin
+    ///                                   # or LowestForRockets
+    /// rocket_cells = 1                  # cells dedicated by LowestForRockets, also
+    ///                                   # { LowestForRockets = { cells = 1 } }
+    /// session_counters = "ResetOnStart" # Preserve if missing
+    ///
+    /// [planner]                         # weights of the Planner strategy
+    /// survival = 10.0
+    /// revenue = 1.0
+    ///
+    /// [reservation]                     # no reservations if missing
+    /// max_cells = 2
+    /// ttl = 10
+    ///
+    /// [service_tiers]                   # no service tiers if missing
+    /// full_service_percent = 60
+    /// priority_service_percent = 40
+    /// priority_explorers = [7, 8]
+    ///
+    /// [energy_budget]                   # no budget if missing
+    /// period = 20
+    ///
+    /// [admission]                       # every explorer served if missing
+    /// max_explorers = 4                 # unlimited if missing
+    /// blacklist = [13]
+    /// min_charged_cells = 1
+    ///
+    /// [log]
+    /// sink = "json_lines"               # global (default), stdout or json_lines
+    /// path = "planet-3.jsonl"           # required by json_lines
+    /// max_bytes = 1048576
+    /// keep = 3
+    /// max_channel = "Info"              # Off disables logging, Trace if missing
+    /// events = { InternalPlanetAction = "Trace", MessagePlanetToExplorer = "Off" }
+    /// ```
+    ///
+    /// The JSON form has the same fields. Names of variants are matched
+    /// ignoring case. `PlanetConfig` and the configuration types in it
+    /// deserialize through the same reader, so they accept the same documents
+    /// when embedded in another serde format.
+    ///
+    /// # Errors
+    /// See [`ConfigError`]. Opening the `json_lines` log file is part of
+    /// loading, so its errors are reported against `log.path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let parse = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => PlanetConfig::from_toml,
+            Some("json") => PlanetConfig::from_json,
+            _ => return Err(ConfigError::UnknownFormat { path: path.to_path_buf() }),
+        };
+        let text = std::fs::read_to_string(path).map_err(|e| ConfigError::Io {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        parse(&text)
+    }
+
+    /// Parses a configuration written in TOML, see [`PlanetConfig::load`].
+    pub fn from_toml(text: &str) -> Result<Self, ConfigError> {
+        let table: toml::Table = toml::from_str(text)
+            .map_err(|e| ConfigError::Syntax { reason: e.message().to_string() })?;
+        // Going through JSON values lets both formats share the same checks
+        let value = serde_json::to_value(table)
+            .map_err(|e| ConfigError::Syntax { reason: e.to_string() })?;
+        PlanetConfig::from_value(&value)
+    }
+
+    /// Parses a configuration written in JSON, see [`PlanetConfig::load`].
+    pub fn from_json(text: &str) -> Result<Self, ConfigError> {
+        let value: Value =
+            serde_json::from_str(text).map_err(|e| ConfigError::Syntax { reason: e.to_string() })?;
+        PlanetConfig::from_value(&value)
+    }
+
+    fn from_value(value: &Value) -> Result<Self, ConfigError> {
+        let root = Table::root(value)?;
+        root.check_keys(&[
+            "planet_id",
+            "planet_type",
+            "basic_resource",
+            "combination_rules",
+            "rocket_strategy",
+            "stockpile_cap",
+            "cell_selection",
+            "rocket_cells",
+            "session_counters",
+            "planner",
+            "reservation",
+            "service_tiers",
+            "energy_budget",
+            "admission",
+            "log",
+        ])?;
+
+        let planet_id = root.u32("planet_id")?.ok_or_else(|| root.invalid("planet_id", "missing"))?;
+        let rocket_strategy = root.parsed::<RocketStrategy>("rocket_strategy")?.unwrap_or_default();
+        let basic_resource = root.variant("basic_resource", BASIC_RESOURCES)?;
+        let mut config = PlanetConfig::new(planet_id, rocket_strategy, basic_resource);
+
+        if let Some(planet_type) = root.variant("planet_type", PLANET_TYPES)? {
+            config.planet_type = planet_type;
+        }
+        if let Some(rules) = root.array("combination_rules")? {
+            config.combination_rules = rules
+                .iter()
+                .enumerate()
+                .map(|(i, rule)| {
+                    parse_variant(&format!("{}[{i}]", root.path("combination_rules")), rule, COMPLEX_RESOURCES)
+                })
+                .collect::<Result<_, _>>()?;
+            let max = max_combination_rules(config.planet_type);
+            if config.combination_rules.len() > max {
+                return Err(root.invalid(
+                    "combination_rules",
+                    &format!("a type {:?} planet has at most {max}", config.planet_type),
+                ));
+            }
+        }
+        if let Some(cap) = root.u32("stockpile_cap")? {
+            if cap == 0 {
+                return Err(root.invalid("stockpile_cap", "must be at least 1"));
+            }
+            config.stockpile_cap = cap;
+        }
+        let rocket_cells = root.u32("rocket_cells")?;
+        match root.cell_selection("cell_selection")? {
+            Some(CellSelection::LowestForRockets { cells }) => {
+                let cells = rocket_cells.map_or(cells, |cells| cells as usize);
+                config.cell_selection = CellSelection::LowestForRockets { cells };
+            }
+            Some(selection) if rocket_cells.is_none() => config.cell_selection = selection,
+            _ if rocket_cells.is_some() => {
+                return Err(root.invalid("rocket_cells", "only used by the LowestForRockets cell selection"));
+            }
+            _ => {}
+        }
+        if let Some(session_counters) = root.variant("session_counters", SESSION_COUNTERS)? {
+            config.session_counters = session_counters;
+        }
+
+        if let Some(planner) = root.table("planner")? {
+            config.planner = planner.planner()?;
+        }
+        if let Some(reservation) = root.table("reservation")? {
+            config.reservation = Some(reservation.reservation()?);
+        }
+        if let Some(tiers) = root.table("service_tiers")? {
+            config.service_tiers = Some(tiers.service_tiers()?);
+        }
+        if let Some(budget) = root.table("energy_budget")? {
+            config.energy_budget = Some(budget.energy_budget()?);
+        }
+        if let Some(admission) = root.table("admission")? {
+            config.explorer_policy = Some(Arc::new(admission.admission()?));
+        }
+
+        if let Some(log) = root.table("log")? {
+            log.check_keys(&["sink", "path", "max_bytes", "keep", "max_channel", "events"])?;
+            config.verbosity = log.verbosity()?;
+            config.log_sink = log.sink()?;
+        }
+
+        Ok(config)
+    }
+}
+
+/// Most combination rules a planet type accepts. Mirrors
+/// `PlanetType::constraints`, whose fields are private.
+fn max_combination_rules(planet_type: PlanetType) -> usize {
+    match planet_type {
+        PlanetType::A | PlanetType::D => 0,
+        PlanetType::B => 1,
+        PlanetType::C => 6,
+    }
+}
+
+const PLANET_TYPES: &[(&str, PlanetType)] = &[
+    ("A", PlanetType::A),
+    ("B", PlanetType::B),
+    ("C", PlanetType::C),
+    ("D", PlanetType::D),
+];
+
+const BASIC_RESOURCES: &[(&str, BasicResourceType)] = &[
+    ("Oxygen", BasicResourceType::Oxygen),
+    ("Hydrogen", BasicResourceType::Hydrogen),
+    ("Carbon", BasicResourceType::Carbon),
+    ("Silicon", BasicResourceType::Silicon),
+];
+
+const COMPLEX_RESOURCES: &[(&str, ComplexResourceType)] = &[
+    ("Diamond", ComplexResourceType::Diamond),
+    ("Water", ComplexResourceType::Water),
+    ("Life", ComplexResourceType::Life),
+    ("Robot", ComplexResourceType::Robot),
+    ("Dolphin", ComplexResourceType::Dolphin),
+    ("AIPartner", ComplexResourceType::AIPartner),
+];
+
+const CELL_SELECTIONS: &[(&str, CellSelection)] = &[
+    ("FirstFull", CellSelection::FirstFull),
+    ("LastFull", CellSelection::LastFull),
+    ("RoundRobin", CellSelection::RoundRobin),
+    ("LowestForRockets", CellSelection::LowestForRockets { cells: 1 }),
+];
+
+const SESSION_COUNTERS: &[(&str, SessionCounters)] = &[
+    ("Preserve", SessionCounters::Preserve),
+    ("ResetOnStart", SessionCounters::ResetOnStart),
+];
+
+/// `None` stands for `Off`.
+const CHANNELS: &[(&str, Option<Channel>)] = &[
+    ("Off", None),
+    ("Error", Some(Channel::Error)),
+    ("Warning", Some(Channel::Warning)),
+    ("Info", Some(Channel::Info)),
+    ("Debug", Some(Channel::Debug)),
+    ("Trace", Some(Channel::Trace)),
+];
+
+const EVENT_TYPES: &[(&str, EventType)] = &[
+    ("MessagePlanetToOrchestrator", EventType::MessagePlanetToOrchestrator),
+    ("MessageOrchestratorToPlanet", EventType::MessageOrchestratorToPlanet),
+    ("MessagePlanetToExplorer", EventType::MessagePlanetToExplorer),
+    ("MessageOrchestratorToExplorer", EventType::MessageOrchestratorToExplorer),
+    ("MessageExplorerToPlanet", EventType::MessageExplorerToPlanet),
+    ("MessageExplorerToOrchestrator", EventType::MessageExplorerToOrchestrator),
+    ("InternalPlanetAction", EventType::InternalPlanetAction),
+    ("InternalExplorerAction", EventType::InternalExplorerAction),
+    ("InternalOrchestratorAction", EventType::InternalOrchestratorAction),
+    ("UserToPlanet", EventType::UserToPlanet),
+    ("UserToExplorer", EventType::UserToExplorer),
+    ("UserToOrchestrator", EventType::UserToOrchestrator),
+];
+
+/// A table of the file, with the path used to name its fields in errors.
+struct Table<'a> {
+    prefix: String,
+    map: &'a Map<String, Value>,
+}
+
+impl<'a> Table<'a> {
+    fn root(value: &'a Value) -> Result<Self, ConfigError> {
+        match value {
+            Value::Object(map) => Ok(Table { prefix: String::new(), map }),
+            _ => Err(ConfigError::Syntax { reason: "expected a table at the top level".to_string() }),
+        }
+    }
+
+    /// The table a type is read from when deserialized alone; errors name
+    /// its fields as in a file, e.g. `reservation.ttl`.
+    fn named(prefix: &str, value: &'a Value) -> Result<Self, ConfigError> {
+        match value {
+            Value::Object(map) => Ok(Table { prefix: prefix.to_string(), map }),
+            _ => Err(ConfigError::Invalid { field: prefix.to_string(), reason: "expected a table".to_string() }),
+        }
+    }
+
+    fn path(&self, key: &str) -> String {
+        if self.prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{key}", self.prefix)
+        }
+    }
+
+    fn invalid(&self, key: &str, reason: &str) -> ConfigError {
+        ConfigError::Invalid { field: self.path(key), reason: reason.to_string() }
+    }
+
+    fn check_keys(&self, known: &[&str]) -> Result<(), ConfigError> {
+        match self.map.keys().find(|k| !known.contains(&k.as_str())) {
+            Some(key) => Err(self.invalid(key, "unknown field")),
+            None => Ok(()),
+        }
+    }
+
+    /// A `null` value counts as missing, as TOML has no `null`.
+    fn get(&self, key: &str) -> Option<&'a Value> {
+        self.map.get(key).filter(|value| !value.is_null())
+    }
+
+    fn table(&self, key: &str) -> Result<Option<Table<'a>>, ConfigError> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::Object(map)) => Ok(Some(Table { prefix: self.path(key), map })),
+            Some(_) => Err(self.invalid(key, "expected a table")),
+        }
+    }
+
+    fn array(&self, key: &str) -> Result<Option<&'a Vec<Value>>, ConfigError> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::Array(values)) => Ok(Some(values)),
+            Some(_) => Err(self.invalid(key, "expected an array")),
+        }
+    }
+
+    fn ids(&self, key: &str) -> Result<Option<HashSet<u32>>, ConfigError> {
+        let Some(ids) = self.array(key)? else {
+            return Ok(None);
+        };
+        ids.iter()
+            .enumerate()
+            .map(|(i, id)| as_u32(&format!("{}[{i}]", self.path(key)), id))
+            .collect::<Result<_, _>>()
+            .map(Some)
+    }
+
+    fn str(&self, key: &str) -> Result<Option<&'a str>, ConfigError> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(Value::String(s)) => Ok(Some(s)),
+            Some(_) => Err(self.invalid(key, "expected a string")),
+        }
+    }
+
+    fn u64(&self, key: &str) -> Result<Option<u64>, ConfigError> {
+        self.get(key).map(|v| as_u64(&self.path(key), v)).transpose()
+    }
+
+    fn u32(&self, key: &str) -> Result<Option<u32>, ConfigError> {
+        self.get(key).map(|v| as_u32(&self.path(key), v)).transpose()
+    }
+
+    fn weight(&self, key: &str) -> Result<Option<f64>, ConfigError> {
+        match self.get(key) {
+            None => Ok(None),
+            Some(value) => match value.as_f64() {
+                Some(weight) if weight.is_finite() && weight >= 0.0 => Ok(Some(weight)),
+                _ => Err(self.invalid(key, "expected a non-negative number")),
+            },
+        }
+    }
+
+    fn percent(&self, key: &str) -> Result<Option<u32>, ConfigError> {
+        match self.u32(key)? {
+            Some(percent) if percent > 100 => Err(self.invalid(key, "expected a percentage, at most 100")),
+            percent => Ok(percent),
+        }
+    }
+
+    fn parsed<T: FromStr<Err = String>>(&self, key: &str) -> Result<Option<T>, ConfigError> {
+        self.str(key)?
+            .map(|s| s.parse().map_err(|reason| ConfigError::Invalid { field: self.path(key), reason }))
+            .transpose()
+    }
+
+    fn variant<T: Clone>(&self, key: &str, variants: &[(&str, T)]) -> Result<Option<T>, ConfigError> {
+        self.get(key).map(|v| parse_variant(&self.path(key), v, variants)).transpose()
+    }
+
+    fn cell_selection(&self, key: &str) -> Result<Option<CellSelection>, ConfigError> {
+        self.get(key).map(|v| parse_cell_selection(&self.path(key), v)).transpose()
+    }
+
+    fn planner(&self) -> Result<PlannerWeights, ConfigError> {
+        self.check_keys(&["survival", "revenue"])?;
+        let defaults = PlannerWeights::default();
+        Ok(PlannerWeights {
+            survival: self.weight("survival")?.unwrap_or(defaults.survival),
+            revenue: self.weight("revenue")?.unwrap_or(defaults.revenue),
+        })
+    }
+
+    fn reservation(&self) -> Result<ReservationPolicy, ConfigError> {
+        self.check_keys(&["max_cells", "ttl"])?;
+        let defaults = ReservationPolicy::default();
+        Ok(ReservationPolicy {
+            max_cells: self.u32("max_cells")?.unwrap_or(defaults.max_cells),
+            ttl: self.u64("ttl")?.unwrap_or(defaults.ttl),
+        })
+    }
+
+    fn service_tiers(&self) -> Result<ServiceTiers, ConfigError> {
+        self.check_keys(&["full_service_percent", "priority_service_percent", "priority_explorers"])?;
+        let mut service_tiers = ServiceTiers::default();
+        if let Some(full) = self.percent("full_service_percent")? {
+            service_tiers.full_service_percent = full;
+        }
+        if let Some(priority) = self.percent("priority_service_percent")? {
+            service_tiers.priority_service_percent = priority;
+        }
+        if service_tiers.priority_service_percent > service_tiers.full_service_percent {
+            return Err(self.invalid("priority_service_percent", "must not exceed full_service_percent"));
+        }
+        if let Some(explorers) = self.ids("priority_explorers")? {
+            service_tiers.priority_explorers = explorers;
+        }
+        Ok(service_tiers)
+    }
+
+    fn energy_budget(&self) -> Result<EnergyBudget, ConfigError> {
+        self.check_keys(&["period"])?;
+        let period = self.u32("period")?.unwrap_or(EnergyBudget::default().period);
+        if period == 0 {
+            return Err(self.invalid("period", "must be at least 1"));
+        }
+        Ok(EnergyBudget { period })
+    }
+
+    fn admission(&self) -> Result<AdmissionPolicy, ConfigError> {
+        self.check_keys(&["max_explorers", "blacklist", "min_charged_cells"])?;
+        Ok(AdmissionPolicy {
+            max_explorers: self.u32("max_explorers")?.map(|max| max as usize),
+            blacklist: self.ids("blacklist")?.unwrap_or_default(),
+            min_charged_cells: self.u32("min_charged_cells")?.unwrap_or(0),
+        })
+    }
+
+    fn verbosity(&self) -> Result<LogVerbosity, ConfigError> {
+        let mut verbosity = match self.variant("max_channel", CHANNELS)? {
+            Some(Some(channel)) => LogVerbosity::new(channel),
+            Some(None) => LogVerbosity::off(),
+            None => LogVerbosity::default(),
+        };
+        if let Some(events) = self.table("events")? {
+            for (name, channel) in events.map {
+                let event_type = parse_name(&events.path(name), name, EVENT_TYPES)?;
+                verbosity = match parse_variant(&events.path(name), channel, CHANNELS)? {
+                    Some(channel) => verbosity.with_event(event_type, channel),
+                    None => verbosity.without_event(event_type),
+                };
+            }
+        }
+        Ok(verbosity)
+    }
+
+    fn sink(&self) -> Result<Option<Arc<dyn LogSink>>, ConfigError> {
+        let sink: Arc<dyn LogSink> = match self.str("sink")? {
+            None | Some("global") => Arc::new(GlobalLogger),
+            Some("stdout") => Arc::new(StdoutSink),
+            Some("json_lines") => {
+                let path = self.str("path")?.ok_or_else(|| self.invalid("path", "required by the json_lines sink"))?;
+                let max_bytes = self.u64("max_bytes")?.unwrap_or(1 << 20);
+                let keep = self.u32("keep")?.unwrap_or(3) as usize;
+                let file = JsonLinesFile::new(path, max_bytes, keep).map_err(|e| self.invalid("path", &e.to_string()))?;
+                Arc::new(file)
+            }
+            Some(other) => {
+                return Err(self.invalid(
+                    "sink",
+                    &format!("unknown sink {other:?}, expected one of global, stdout, json_lines"),
+                ));
+            }
+        };
+        Ok(Some(sink))
+    }
+}
+
+fn as_u64(field: &str, value: &Value) -> Result<u64, ConfigError> {
+    value.as_u64().ok_or_else(|| ConfigError::Invalid {
+        field: field.to_string(),
+        reason: "expected a non-negative integer".to_string(),
+    })
+}
+
+fn as_u32(field: &str, value: &Value) -> Result<u32, ConfigError> {
+    u32::try_from(as_u64(field, value)?).map_err(|_| ConfigError::Invalid {
+        field: field.to_string(),
+        reason: format!("must be at most {}", u32::MAX),
+    })
+}
+
+/// A name of [`CELL_SELECTIONS`], or `{ LowestForRockets = { cells = 2 } }`
+/// as `CellSelection` serializes it.
+fn parse_cell_selection(field: &str, value: &Value) -> Result<CellSelection, ConfigError> {
+    let Value::Object(map) = value else {
+        return parse_variant(field, value, CELL_SELECTIONS);
+    };
+    let lowest = Table { prefix: field.to_string(), map };
+    lowest.check_keys(&["LowestForRockets"])?;
+    let Some(table) = lowest.table("LowestForRockets")? else {
+        return Err(ConfigError::Invalid { field: field.to_string(), reason: "expected a string".to_string() });
+    };
+    table.check_keys(&["cells"])?;
+    let cells = table.u32("cells")?.unwrap_or(1) as usize;
+    Ok(CellSelection::LowestForRockets { cells })
+}
+
+fn parse_variant<T: Clone>(field: &str, value: &Value, variants: &[(&str, T)]) -> Result<T, ConfigError> {
+    match value {
+        Value::String(name) => parse_name(field, name, variants),
+        _ => Err(ConfigError::Invalid { field: field.to_string(), reason: "expected a string".to_string() }),
+    }
+}
+
+fn parse_name<T: Clone>(field: &str, name: &str, variants: &[(&str, T)]) -> Result<T, ConfigError> {
+    variants
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(_, v)| v.clone())
+        .ok_or_else(|| {
+            let names: Vec<_> = variants.iter().map(|(n, _)| *n).collect();
+            ConfigError::Invalid {
+                field: field.to_string(),
+                reason: format!("unknown value {name:?}, expected one of {}", names.join(", ")),
+            }
+        })
+}
+
+/// Reads `value` with `read`, the reader of the configuration files, so that
+/// a type accepts the same documents whether it is deserialized alone or
+/// loaded as part of a file.
+fn deserialize_with<'de, D, T>(
+    deserializer: D,
+    read: impl FnOnce(&Value) -> Result<T, ConfigError>,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Value::deserialize(deserializer)?;
+    read(&value).map_err(de::Error::custom)
+}
+
+/// A whole configuration file. There is no `Serialize`: log sinks and
+/// explorer policies are not data.
+impl<'de> Deserialize<'de> for PlanetConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with(deserializer, PlanetConfig::from_value)
+    }
+}
+
+/// A name, ignoring case, see [`RocketStrategy::from_str`].
+impl<'de> Deserialize<'de> for RocketStrategy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for CellSelection {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with(deserializer, |value| parse_cell_selection("cell_selection", value))
+    }
+}
+
+impl<'de> Deserialize<'de> for SessionCounters {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with(deserializer, |value| parse_variant("session_counters", value, SESSION_COUNTERS))
+    }
+}
+
+impl<'de> Deserialize<'de> for PlannerWeights {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with(deserializer, |value| Table::named("planner", value)?.planner())
+    }
+}
+
+impl<'de> Deserialize<'de> for ReservationPolicy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with(deserializer, |value| Table::named("reservation", value)?.reservation())
+    }
+}
+
+impl<'de> Deserialize<'de> for ServiceTiers {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with(deserializer, |value| Table::named("service_tiers", value)?.service_tiers())
+    }
+}
+
+impl<'de> Deserialize<'de> for EnergyBudget {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with(deserializer, |value| Table::named("energy_budget", value)?.energy_budget())
+    }
+}
+
+impl<'de> Deserialize<'de> for AdmissionPolicy {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_with(deserializer, |value| Table::named("admission", value)?.admission())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid(field: &str) -> impl Fn(&ConfigError) -> bool + '_ {
+        move |e| matches!(e, ConfigError::Invalid { field: f, .. } if f == field)
+    }
+
+    #[test]
+    fn test_toml_and_json_give_the_same_config() {
+        let toml = r#"
+            planet_id = 3
+            planet_type = "c"
+            basic_resource = "Oxygen"
+            combination_rules = ["Water", "Diamond"]
+            rocket_strategy = "emergencyreserve"
+            session_counters = "ResetOnStart"
+            cell_selection = "lowestforrockets"
+            rocket_cells = 2
+
+            [planner]
+            survival = 4
+
+            [reservation]
+            max_cells = 2
+
+            [service_tiers]
+            priority_explorers = [7]
+
+            [admission]
+            max_explorers = 2
+            blacklist = [13]
+
+            [log]
+            sink = "stdout"
+            max_channel = "Info"
+            events = { InternalPlanetAction = "Trace", MessagePlanetToExplorer = "Off" }
+        "#;
+        let json = r#"{
+            "planet_id": 3,
+            "planet_type": "C",
+            "basic_resource": "oxygen",
+            "combination_rules": ["Water", "Diamond"],
+            "rocket_strategy": "EmergencyReserve",
+            "session_counters": "ResetOnStart",
+            "cell_selection": "LowestForRockets",
+            "rocket_cells": 2,
+            "planner": { "survival": 4.0 },
+            "reservation": { "max_cells": 2 },
+            "service_tiers": { "priority_explorers": [7] },
+            "admission": { "max_explorers": 2, "blacklist": [13] },
+            "log": {
+                "sink": "stdout",
+                "max_channel": "Info",
+                "events": { "InternalPlanetAction": "Trace", "MessagePlanetToExplorer": "Off" }
+            }
+        }"#;
+
+        for config in [PlanetConfig::from_toml(toml).unwrap(), PlanetConfig::from_json(json).unwrap()] {
+            assert_eq!(config.planet_id, 3);
+            assert!(matches!(config.planet_type, PlanetType::C));
+            assert_eq!(config.basic_resource, Some(BasicResourceType::Oxygen));
+            assert_eq!(config.combination_rules, vec![ComplexResourceType::Water, ComplexResourceType::Diamond]);
+            assert_eq!(config.rocket_strategy, RocketStrategy::EmergencyReserve);
+            assert_eq!(config.session_counters, SessionCounters::ResetOnStart);
+            assert_eq!(config.planner, PlannerWeights { survival: 4.0, revenue: 1.0 });
+            assert_eq!(config.cell_selection, CellSelection::LowestForRockets { cells: 2 });
+            assert_eq!(config.reservation, Some(ReservationPolicy { max_cells: 2, ..ReservationPolicy::default() }));
+            assert_eq!(config.service_tiers, Some(ServiceTiers::new([7])));
+            let admission = AdmissionPolicy {
+                max_explorers: Some(2),
+                blacklist: [13].into(),
+                min_charged_cells: 0,
+            };
+            assert_eq!(format!("{:?}", config.explorer_policy), format!("{:?}", Some(admission)));
+
+            let verbosity = &config.verbosity;
+            assert!(verbosity.enabled(&EventType::InternalPlanetAction, &Channel::Trace));
+            assert!(!verbosity.enabled(&EventType::MessagePlanetToExplorer, &Channel::Error));
+            assert!(verbosity.enabled(&EventType::MessagePlanetToOrchestrator, &Channel::Info));
+            assert!(!verbosity.enabled(&EventType::MessagePlanetToOrchestrator, &Channel::Debug));
+            assert!(config.log_sink.is_some());
+        }
+    }
+
+    #[test]
+    fn test_minimal_config_uses_the_defaults() {
+        let config = PlanetConfig::from_toml("planet_id = 1").unwrap();
+        assert!(matches!(config.planet_type, PlanetType::A));
+        assert_eq!(config.rocket_strategy, RocketStrategy::Default);
+        assert_eq!(config.basic_resource, None);
+        assert!(config.combination_rules.is_empty());
+        assert_eq!(config.reservation, None);
+        assert_eq!(config.service_tiers, None);
+        assert!(config.explorer_policy.is_none());
+        assert!(config.log_sink.is_none());
+    }
+
+    #[test]
+    fn test_errors_point_at_the_field() {
+        let cases = [
+            ("rocket_strategy = \"Safe\"", "planet_id"),
+            ("planet_id = -1", "planet_id"),
+            ("planet_id = 1\nrocket_strategy = \"Reckless\"", "rocket_strategy"),
+            ("planet_id = 1\nstockpile_cap = 0", "stockpile_cap"),
+            ("planet_id = 1\ncombination_rules = [\"Water\", 4]", "combination_rules[1]"),
+            ("planet_id = 1\ncombination_rules = [\"Water\"]", "combination_rules"),
+            ("planet_id = 1\nreservation = 3", "reservation"),
+            ("planet_id = 1\n[reservation]\nmax_cells = \"two\"", "reservation.max_cells"),
+            ("planet_id = 1\n[reservation]\nmax_cell = 2", "reservation.max_cell"),
+            ("planet_id = 1\n[service_tiers]\nfull_service_percent = 120", "service_tiers.full_service_percent"),
+            ("planet_id = 1\n[service_tiers]\npriority_service_percent = 80", "service_tiers.priority_service_percent"),
+            ("planet_id = 1\n[log]\nsink = \"json_lines\"", "log.path"),
+            ("planet_id = 1\n[planner]\nrevenue = -2.5", "planner.revenue"),
+            ("planet_id = 1\ncell_selection = \"LastFull\"\nrocket_cells = 2", "rocket_cells"),
+            ("planet_id = 1\n[energy_budget]\nperiod = 0", "energy_budget.period"),
+            ("planet_id = 1\n[admission]\nblacklist = [-1]", "admission.blacklist[0]"),
+            ("planet_id = 1\n[log.events]\nSunray = \"Off\"", "log.events.Sunray"),
+        ];
+        for (toml, field) in cases {
+            let err = PlanetConfig::from_toml(toml).unwrap_err();
+            assert!(invalid(field)(&err), "{toml:?} gave {err}");
+        }
+
+        let err = PlanetConfig::from_json(r#"{ "planet_id": 1, "log": { "max_channel": "Loud" } }"#).unwrap_err();
+        assert!(invalid("log.max_channel")(&err), "{err}");
+        assert!(err.to_string().starts_with("log.max_channel: unknown value \"Loud\""), "{err}");
+    }
+
+    #[test]
+    fn test_planet_from_file() {
+        // SCENARIO: A planet built from a file on disk runs with the configured type and rules.
+        let dir = std::env::temp_dir().join(format!("planet-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("planet.toml");
+        std::fs::write(&path, "planet_id = 9\nplanet_type = \"C\"\ncombination_rules = [\"Water\"]\n").unwrap();
+
+        let (_tx_orchestrator, rx_orchestrator) = crossbeam_channel::unbounded();
+        let (tx_planet, _rx_planet) = crossbeam_channel::unbounded();
+        let (_tx_explorer, rx_explorer) = crossbeam_channel::unbounded();
+        let planet = crate::create_planet_from_file(&path, rx_orchestrator, tx_planet, rx_explorer).unwrap();
+        assert_eq!(planet.id(), 9);
+        assert_eq!(planet.combinator().all_available_recipes(), [ComplexResourceType::Water].into());
+
+        let mut handle = crate::PlanetHandle::spawn(PlanetConfig::load(&path).unwrap()).unwrap();
+        handle.start().unwrap();
+        assert_eq!(handle.internal_state().unwrap().energy_cells.len(), 1);
+        handle.kill().unwrap();
+        handle.join().unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_config_embedded_in_another_document() {
+        // SCENARIO: A planet configuration nested in a larger serde document is
+        // read like a file, with the same checks and field names.
+        #[derive(serde::Deserialize)]
+        struct Galaxy {
+            planets: Vec<PlanetConfig>,
+        }
+        let json = r#"{ "planets": [
+            { "planet_id": 1, "rocket_strategy": "safe" },
+            { "planet_id": 2, "cell_selection": { "LowestForRockets": { "cells": 2 } } }
+        ] }"#;
+        let galaxy: Galaxy = serde_json::from_str(json).unwrap();
+        assert_eq!(galaxy.planets[0].rocket_strategy, RocketStrategy::Safe);
+        assert_eq!(galaxy.planets[1].cell_selection, CellSelection::LowestForRockets { cells: 2 });
+
+        let galaxy: Galaxy = toml::from_str("[[planets]]\nplanet_id = 3\n[planets.reservation]\nttl = 4\n").unwrap();
+        assert_eq!(galaxy.planets[0].reservation, Some(ReservationPolicy { ttl: 4, ..Default::default() }));
+
+        let err = serde_json::from_str::<Galaxy>(r#"{ "planets": [{ "planet_id": 1, "energy_budget": { "period": 0 } }] }"#)
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("energy_budget.period: must be at least 1"), "{err}");
+        let err = serde_json::from_str::<ServiceTiers>(r#"{ "full_service_percent": 101 }"#).unwrap_err();
+        assert!(err.to_string().starts_with("service_tiers.full_service_percent"), "{err}");
+    }
+
+    #[test]
+    fn test_syntax_and_file_errors() {
+        assert!(matches!(PlanetConfig::from_toml("planet_id = "), Err(ConfigError::Syntax { .. })));
+        assert!(matches!(PlanetConfig::from_json("[1, 2]"), Err(ConfigError::Syntax { .. })));
+        assert!(matches!(PlanetConfig::load("planet.yaml"), Err(ConfigError::UnknownFormat { .. })));
+        assert!(matches!(PlanetConfig::load("does/not/exist.toml"), Err(ConfigError::Io { .. })));
+    }
+}
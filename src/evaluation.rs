@@ -0,0 +1,285 @@
+use crate::{PlanetConfig, RocketStrategy, SimulatedPlanet};
+use common_game::components::resource::BasicResourceType;
+use common_game::protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer};
+use std::fmt::{Display, Formatter};
+
+const SIMULATED_PLANET_ID: u32 = 1;
+const SIMULATED_EXPLORER_ID: u32 = 1;
+
+/// Relative weights of the events hitting a simulated planet at each step.
+/// An explorer request is an availability query followed, if any cell is
+/// available, by a generation request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventDistribution {
+    pub sunray: u32,
+    pub asteroid: u32,
+    pub explorer_request: u32,
+}
+
+impl Default for EventDistribution {
+    fn default() -> Self {
+        EventDistribution {
+            sunray: 6,
+            asteroid: 1,
+            explorer_request: 3,
+        }
+    }
+}
+
+/// Parameters of a strategy comparison run.
+///
+/// - `games`: games simulated for every strategy/resource pair
+/// - `max_events`: a planet still alive after this many events survived the game
+/// - `seed`: game `i` is seeded with `seed + i`, so every strategy faces the same games
+/// - `distribution`: see [`EventDistribution`]
+/// - `demand`: resources asked by explorers, drawn uniformly (repeat an entry to weight it)
+/// - `strategies` and `resources`: the planets to compare
+#[derive(Debug, Clone)]
+pub struct EvaluationParams {
+    pub games: u32,
+    pub max_events: u32,
+    pub seed: u64,
+    pub distribution: EventDistribution,
+    pub demand: Vec<BasicResourceType>,
+    pub strategies: Vec<RocketStrategy>,
+    pub resources: Vec<BasicResourceType>,
+}
+
+impl Default for EvaluationParams {
+    fn default() -> Self {
+        let all_resources = vec![
+            BasicResourceType::Oxygen,
+            BasicResourceType::Hydrogen,
+            BasicResourceType::Carbon,
+            BasicResourceType::Silicon,
+        ];
+        EvaluationParams {
+            games: 1000,
+            max_events: 200,
+            seed: 0x5eed,
+            distribution: EventDistribution::default(),
+            demand: all_resources.clone(),
+            strategies: RocketStrategy::iter().collect(),
+            resources: all_resources,
+        }
+    }
+}
+
+/// Aggregated outcome of the games played by one strategy/resource pair.
+#[derive(Debug, Clone)]
+pub struct StrategyScore {
+    pub strategy: RocketStrategy,
+    pub resource: BasicResourceType,
+    pub games: u32,
+    pub survived: u32,
+    pub total_lifetime: u64,
+    pub resources_delivered: u64,
+    pub energy_wasted: u64,
+}
+
+impl StrategyScore {
+    pub fn survival_rate(&self) -> f64 {
+        self.survived as f64 / self.games.max(1) as f64
+    }
+
+    /// Average number of events a planet lived through.
+    pub fn average_lifetime(&self) -> f64 {
+        self.total_lifetime as f64 / self.games.max(1) as f64
+    }
+
+    /// Average number of resources handed to explorers per game.
+    pub fn average_delivered(&self) -> f64 {
+        self.resources_delivered as f64 / self.games.max(1) as f64
+    }
+
+    /// Average number of sunrays per game that found every cell charged and were lost.
+    pub fn average_wasted(&self) -> f64 {
+        self.energy_wasted as f64 / self.games.max(1) as f64
+    }
+}
+
+/// Result of [`evaluate_strategies`], one row per strategy/resource pair.
+/// `Display` renders it as a text table, [`ComparisonTable::to_csv`] as CSV.
+#[derive(Debug, Clone)]
+pub struct ComparisonTable {
+    pub rows: Vec<StrategyScore>,
+}
+
+impl ComparisonTable {
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "strategy,resource,games,survival_rate,average_lifetime,resources_delivered,energy_wasted\n",
+        );
+        for r in &self.rows {
+            out.push_str(&format!(
+                "{},{:?},{},{:.4},{:.2},{:.2},{:.2}\n",
+                r.strategy,
+                r.resource,
+                r.games,
+                r.survival_rate(),
+                r.average_lifetime(),
+                r.average_delivered(),
+                r.average_wasted(),
+            ));
+        }
+        out
+    }
+}
+
+impl Display for ComparisonTable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<18} {:<10} {:>7} {:>9} {:>10} {:>10} {:>8}",
+            "strategy", "resource", "games", "survival", "lifetime", "delivered", "wasted"
+        )?;
+        for r in &self.rows {
+            writeln!(
+                f,
+                "{:<18} {:<10} {:>7} {:>8.1}% {:>10.2} {:>10.2} {:>8.2}",
+                r.strategy.to_string(),
+                format!("{:?}", r.resource),
+                r.games,
+                r.survival_rate() * 100.0,
+                r.average_lifetime(),
+                r.average_delivered(),
+                r.average_wasted(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays `params.games` seeded games for every strategy/resource pair on a
+/// [`SimulatedPlanet`] and returns the comparison table.
+///
+/// # Errors
+/// Returns an error if a planet cannot be built for one of the resources.
+pub fn evaluate_strategies(params: &EvaluationParams) -> Result<ComparisonTable, String> {
+    let mut rows = Vec::new();
+    for resource in &params.resources {
+        for strategy in &params.strategies {
+            let config = PlanetConfig::new(SIMULATED_PLANET_ID, strategy.clone(), Some(*resource));
+            let mut score = StrategyScore {
+                strategy: strategy.clone(),
+                resource: *resource,
+                games: params.games,
+                survived: 0,
+                total_lifetime: 0,
+                resources_delivered: 0,
+                energy_wasted: 0,
+            };
+            for game in 0..params.games {
+                let planet = SimulatedPlanet::new(config.clone())?;
+                let outcome = play_game(planet, params, params.seed.wrapping_add(game as u64));
+                if outcome.survived {
+                    score.survived += 1;
+                }
+                score.total_lifetime += outcome.lifetime as u64;
+                score.resources_delivered += outcome.delivered as u64;
+                score.energy_wasted += outcome.wasted as u64;
+            }
+            rows.push(score);
+        }
+    }
+    Ok(ComparisonTable { rows })
+}
+
+struct GameOutcome {
+    survived: bool,
+    lifetime: u32,
+    delivered: u32,
+    wasted: u32,
+}
+
+fn play_game(mut planet: SimulatedPlanet, params: &EvaluationParams, seed: u64) -> GameOutcome {
+    let mut rng = SplitMix64(seed);
+    let d = &params.distribution;
+    let total_weight = (d.sunray + d.asteroid + d.explorer_request).max(1) as u64;
+
+    let mut sunrays = 0;
+    let mut delivered = 0;
+    let mut survived = true;
+    let mut lifetime = 0;
+    while lifetime < params.max_events {
+        lifetime += 1;
+        let roll = (rng.next() % total_weight) as u32;
+        if roll < d.sunray {
+            sunrays += 1;
+            planet.sunray();
+        } else if roll < d.sunray + d.asteroid {
+            if planet.asteroid().is_none() {
+                survived = false;
+                break;
+            }
+        } else if !params.demand.is_empty() {
+            let resource = params.demand[(rng.next() % params.demand.len() as u64) as usize];
+            let available = planet.explorer_msg(ExplorerToPlanet::AvailableEnergyCellRequest {
+                explorer_id: SIMULATED_EXPLORER_ID,
+            });
+            if let Some(PlanetToExplorer::AvailableEnergyCellResponse { available_cells }) = available
+                && available_cells > 0
+                && let Some(PlanetToExplorer::GenerateResourceResponse { resource: Some(_) }) =
+                    planet.explorer_msg(ExplorerToPlanet::GenerateResourceRequest {
+                        explorer_id: SIMULATED_EXPLORER_ID,
+                        resource,
+                    })
+            {
+                delivered += 1;
+            }
+        }
+    }
+
+    // Every sunray that was not lost is still in a cell, in a rocket or in a resource.
+    let stored = planet.state.charged_count() + planet.state.rockets_built() + delivered;
+    GameOutcome {
+        survived,
+        lifetime,
+        delivered,
+        wasted: sunrays - stored.min(sunrays),
+    }
+}
+
+/// Small deterministic generator, so that a seed always replays the same game.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn small_params() -> EvaluationParams {
+        EvaluationParams {
+            games: 50,
+            resources: vec![BasicResourceType::Hydrogen],
+            ..EvaluationParams::default()
+        }
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_table() {
+        let first = evaluate_strategies(&small_params()).unwrap().to_csv();
+        let second = evaluate_strategies(&small_params()).unwrap().to_csv();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_disabled_never_outlives_safe() {
+        let table = evaluate_strategies(&small_params()).unwrap();
+        let score = |s: RocketStrategy| table.rows.iter().find(|r| r.strategy == s).unwrap().clone();
+
+        let disabled = score(RocketStrategy::Disabled);
+        let safe = score(RocketStrategy::Safe);
+        assert_eq!(disabled.survived, 0, "Disabled cannot survive an asteroid");
+        assert!(safe.average_lifetime() >= disabled.average_lifetime());
+    }
+}
@@ -0,0 +1,339 @@
+use crate::generation::{BatchStop, GeneratedBatch};
+use crate::handle::{PlanetError, PlanetHandle};
+use common_game::components::resource::{
+    BasicResource, BasicResourceType, ComplexResource, ComplexResourceRequest, ComplexResourceType,
+    GenericResource,
+};
+use common_game::protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer, PlanetToExplorerKind};
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Result of a combination, as sent by the planet: the complex resource, or
+/// the reason and the two ingredients given back.
+pub type CombineResult = Result<ComplexResource, (String, GenericResource, GenericResource)>;
+
+/// The explorer side of the planet protocol.
+///
+/// ```no_run
+/// use common_game::components::resource::BasicResourceType;
+/// use Planet::{ExplorerClient, PlanetConfig, PlanetHandle, RocketStrategy};
+///
+/// let planet = PlanetHandle::spawn(PlanetConfig::new(1, RocketStrategy::Safe, None))?;
+/// planet.start()?;
+/// let explorer = ExplorerClient::connect(&planet, 42)?;
+/// if explorer.available_cells()? > 0 {
+///     let hydrogen = explorer.generate(BasicResourceType::Hydrogen)?;
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+/// A planet gives no answer to a request it refuses, so after `generate` and
+/// `combine` the client also sends a `SupportedCombinationRequest`: when its
+/// response comes first the request was refused, and the call returns `None`
+/// right away instead of waiting for the timeout.
+///
+/// An explorer the planet's [`ExplorerPolicy`](crate::ExplorerPolicy)
+/// rejects never gets a client: [`connect`](Self::connect) fails with
+/// [`PlanetError::Rejected`]. A planet that stops answering, even the barrier,
+/// makes the call fail with [`PlanetError::Timeout`] instead.
+#[derive(Debug)]
+pub struct ExplorerClient {
+    planet_id: u32,
+    explorer_id: u32,
+    to_planet: Sender<ExplorerToPlanet>,
+    from_planet: Receiver<PlanetToExplorer>,
+    timeout: Duration,
+}
+
+impl ExplorerClient {
+    /// Lands the explorer on the planet: the orchestrator side of `planet`
+    /// hands the planet the channel to answer this explorer on.
+    ///
+    /// # Errors
+    /// Fails if the planet is stopped, disconnected or rejects the explorer.
+    pub fn connect(planet: &PlanetHandle, explorer_id: u32) -> Result<Self, PlanetError> {
+        let (to_explorer, from_planet) = unbounded();
+        planet.incoming_explorer(explorer_id, to_explorer)?;
+        Ok(ExplorerClient {
+            planet_id: planet.id(),
+            explorer_id,
+            to_planet: planet.explorer_sender().clone(),
+            from_planet,
+            timeout: Duration::from_secs(1),
+        })
+    }
+
+    /// How long each call waits for the planet, one second by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn explorer_id(&self) -> u32 {
+        self.explorer_id
+    }
+
+    pub fn supported_resources(&self) -> Result<HashSet<BasicResourceType>, PlanetError> {
+        let msg = ExplorerToPlanet::SupportedResourceRequest { explorer_id: self.explorer_id };
+        self.send(msg)?;
+        self.reply(self.deadline(), "SupportedResourceRequest", |reply| match reply {
+            PlanetToExplorer::SupportedResourceResponse { resource_list } => Ok(resource_list),
+            other => Err(other),
+        })
+    }
+
+    pub fn supported_combinations(&self) -> Result<HashSet<ComplexResourceType>, PlanetError> {
+        self.send(self.barrier())?;
+        self.reply(self.deadline(), "SupportedCombinationRequest", |reply| match reply {
+            PlanetToExplorer::SupportedCombinationResponse { combination_list } => Ok(combination_list),
+            other => Err(other),
+        })
+    }
+
+    /// Number of charged cells the planet makes available to this explorer.
+    pub fn available_cells(&self) -> Result<u32, PlanetError> {
+        let msg = ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: self.explorer_id };
+        self.send(msg)?;
+        self.reply(self.deadline(), "AvailableEnergyCellRequest", |reply| match reply {
+            PlanetToExplorer::AvailableEnergyCellResponse { available_cells } => Ok(available_cells),
+            other => Err(other),
+        })
+    }
+
+    /// Asks the planet to generate `resource`; `None` if it refused.
+    pub fn generate(&self, resource: BasicResourceType) -> Result<Option<BasicResource>, PlanetError> {
+        let msg = ExplorerToPlanet::GenerateResourceRequest { explorer_id: self.explorer_id, resource };
+        let generated = self.request_or_refused(msg, "GenerateResourceRequest", |reply| match reply {
+            PlanetToExplorer::GenerateResourceResponse { resource } => Ok(resource),
+            other => Err(other),
+        })?;
+        Ok(generated.flatten())
+    }
+
+    /// Generates up to `quantity` units of `resource`, one request at a time,
+    /// and tells why it stopped.
+    ///
+    /// Unlike [`PlanetCoreThinkingModel::generate_batch`](crate::PlanetCoreThinkingModel::generate_batch)
+    /// the units are separate requests, so sunrays and other explorers can
+    /// come in between. Each unit is preceded by `available_cells`, which
+    /// also takes a reservation if the planet grants them.
+    pub fn generate_batch(&self, resource: BasicResourceType, quantity: u32) -> Result<GeneratedBatch, PlanetError> {
+        let mut resources = Vec::new();
+        if !self.supported_resources()?.contains(&resource) {
+            return Ok(GeneratedBatch { resources, stop: BatchStop::Unsupported });
+        }
+        let stop = loop {
+            if resources.len() as u32 == quantity {
+                break BatchStop::Completed;
+            }
+            if self.available_cells()? == 0 {
+                break BatchStop::NoFreeCell;
+            }
+            match self.generate(resource)? {
+                Some(generated) => resources.push(generated),
+                None => break BatchStop::Refused,
+            }
+        };
+        Ok(GeneratedBatch { resources, stop })
+    }
+
+    /// Asks the planet to combine the ingredients in `request`; `None` if it
+    /// refused without answering, in which case the ingredients are lost.
+    pub fn combine(&self, request: ComplexResourceRequest) -> Result<Option<CombineResult>, PlanetError> {
+        let msg = ExplorerToPlanet::CombineResourceRequest { explorer_id: self.explorer_id, msg: request };
+        self.request_or_refused(msg, "CombineResourceRequest", |reply| match reply {
+            PlanetToExplorer::CombineResourceResponse { complex_response } => Ok(complex_response),
+            other => Err(other),
+        })
+    }
+
+    fn barrier(&self) -> ExplorerToPlanet {
+        ExplorerToPlanet::SupportedCombinationRequest { explorer_id: self.explorer_id }
+    }
+
+    fn deadline(&self) -> Instant {
+        Instant::now() + self.timeout
+    }
+
+    fn send(&self, msg: ExplorerToPlanet) -> Result<(), PlanetError> {
+        self.to_planet
+            .send(msg)
+            .map_err(|_| PlanetError::Disconnected { planet_id: self.planet_id })
+    }
+
+    /// Sends `msg` followed by the barrier and tells which one was answered
+    /// first; the barrier response is always consumed.
+    fn request_or_refused<T>(
+        &self,
+        msg: ExplorerToPlanet,
+        request: &'static str,
+        expected: impl FnOnce(PlanetToExplorer) -> Result<T, PlanetToExplorer>,
+    ) -> Result<Option<T>, PlanetError> {
+        self.send(msg)?;
+        self.send(self.barrier())?;
+        let deadline = self.deadline();
+        let answered = self.reply(deadline, request, |reply| match reply {
+            PlanetToExplorer::SupportedCombinationResponse { .. } => Ok(None),
+            other => expected(other).map(Some),
+        });
+        match answered {
+            Ok(Some(value)) => {
+                self.reply(deadline, "SupportedCombinationRequest", |reply| match reply {
+                    PlanetToExplorer::SupportedCombinationResponse { .. } => Ok(()),
+                    other => Err(other),
+                })?;
+                Ok(Some(value))
+            }
+            Ok(None) => Ok(None),
+            Err(PlanetError::Stopped { planet_id }) => {
+                // The barrier was answered too; its reply is no longer relevant
+                let _ = self.from_planet.recv_deadline(deadline);
+                Err(PlanetError::Stopped { planet_id })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    fn reply<T>(
+        &self,
+        deadline: Instant,
+        request: &'static str,
+        expected: impl FnOnce(PlanetToExplorer) -> Result<T, PlanetToExplorer>,
+    ) -> Result<T, PlanetError> {
+        let planet_id = self.planet_id;
+        let reply = self.from_planet.recv_deadline(deadline).map_err(|e| match e {
+            RecvTimeoutError::Timeout => PlanetError::Timeout { planet_id, request },
+            RecvTimeoutError::Disconnected => PlanetError::Disconnected { planet_id },
+        })?;
+        expected(reply).map_err(|other| match other {
+            PlanetToExplorer::Stopped => PlanetError::Stopped { planet_id },
+            other => PlanetError::UnexpectedExplorerReply {
+                planet_id,
+                request,
+                reply: PlanetToExplorerKind::from(&other),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AdmissionPolicy, PlanetConfig, RocketStrategy};
+    use common_game::components::sunray::Sunray;
+
+    fn spawn(strategy: RocketStrategy) -> PlanetHandle {
+        let planet = PlanetHandle::spawn(PlanetConfig::new(5, strategy, Some(BasicResourceType::Silicon)))
+            .expect("Failed to spawn planet");
+        planet.start().unwrap();
+        planet
+    }
+
+    #[test]
+    fn test_explorer_session() {
+        // SCENARIO: An explorer lands, looks around and generates the planet resource.
+        let mut planet = spawn(RocketStrategy::Default);
+        let explorer = ExplorerClient::connect(&planet, 42).unwrap();
+
+        assert_eq!(explorer.supported_resources().unwrap(), HashSet::from([BasicResourceType::Silicon]));
+        assert!(explorer.supported_combinations().unwrap().is_empty());
+        assert_eq!(explorer.available_cells().unwrap(), 0);
+
+        planet.send_sunray(Sunray::default()).unwrap();
+        assert_eq!(explorer.available_cells().unwrap(), 1);
+        let silicon = explorer.generate(BasicResourceType::Silicon).unwrap();
+        assert!(matches!(silicon, Some(BasicResource::Silicon(_))));
+
+        planet.kill().unwrap();
+        planet.join().unwrap();
+    }
+
+    #[test]
+    fn test_refusals_do_not_wait_for_the_timeout() {
+        // SCENARIO: Unsupported and uncharged requests return `None` without a reply.
+        let mut planet = spawn(RocketStrategy::Default);
+        let explorer = ExplorerClient::connect(&planet, 42)
+            .unwrap()
+            .with_timeout(Duration::from_secs(30));
+
+        let started = Instant::now();
+        assert!(explorer.generate(BasicResourceType::Silicon).unwrap().is_none());
+        planet.send_sunray(Sunray::default()).unwrap();
+        assert!(explorer.generate(BasicResourceType::Oxygen).unwrap().is_none());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // The barrier replies were consumed, the client is still in sync
+        assert_eq!(explorer.available_cells().unwrap(), 1);
+
+        planet.kill().unwrap();
+        planet.join().unwrap();
+    }
+
+    #[test]
+    fn test_generate_batch() {
+        // SCENARIO: A batch stops when the cells run out, or right away for a
+        // resource the planet does not generate.
+        let mut planet = spawn(RocketStrategy::Disabled);
+        let explorer = ExplorerClient::connect(&planet, 42).unwrap();
+        for _ in 0..3 {
+            planet.send_sunray(Sunray::default()).unwrap();
+        }
+
+        let batch = explorer.generate_batch(BasicResourceType::Silicon, 2).unwrap();
+        assert_eq!((batch.resources.len(), batch.stop), (2, BatchStop::Completed));
+        let batch = explorer.generate_batch(BasicResourceType::Silicon, 5).unwrap();
+        assert_eq!((batch.resources.len(), batch.stop), (1, BatchStop::NoFreeCell));
+        assert!(batch.resources.iter().all(|r| matches!(r, BasicResource::Silicon(_))));
+        let batch = explorer.generate_batch(BasicResourceType::Oxygen, 1).unwrap();
+        assert_eq!((batch.resources.len(), batch.stop), (0, BatchStop::Unsupported));
+
+        planet.kill().unwrap();
+        planet.join().unwrap();
+    }
+
+    #[test]
+    fn test_rejected_explorer() {
+        // SCENARIO: A blacklisted explorer can't connect, and its landing
+        // does not take the place of another one.
+        let policy = AdmissionPolicy { max_explorers: Some(1), blacklist: HashSet::from([13]), ..Default::default() };
+        let config = PlanetConfig::new(5, RocketStrategy::Default, Some(BasicResourceType::Silicon))
+            .with_explorer_policy(policy);
+        let mut planet = PlanetHandle::spawn(config).expect("Failed to spawn planet");
+        planet.start().unwrap();
+
+        let started = Instant::now();
+        assert_eq!(
+            ExplorerClient::connect(&planet, 13).unwrap_err(),
+            PlanetError::Rejected { planet_id: 5, reason: "blacklisted".to_string() }
+        );
+        assert!(started.elapsed() < Duration::from_millis(500));
+
+        let explorer = ExplorerClient::connect(&planet, 42).unwrap();
+        planet.send_sunray(Sunray::default()).unwrap();
+        assert!(explorer.generate(BasicResourceType::Silicon).unwrap().is_some());
+        assert!(matches!(
+            ExplorerClient::connect(&planet, 43).unwrap_err(),
+            PlanetError::Rejected { planet_id: 5, .. }
+        ));
+
+        planet.kill().unwrap();
+        planet.join().unwrap();
+    }
+
+    #[test]
+    fn test_stopped_planet() {
+        let mut planet = spawn(RocketStrategy::Default);
+        let explorer = ExplorerClient::connect(&planet, 42).unwrap();
+        planet.stop().unwrap();
+
+        assert_eq!(
+            explorer.generate(BasicResourceType::Silicon).unwrap_err(),
+            PlanetError::Stopped { planet_id: 5 }
+        );
+        assert_eq!(explorer.available_cells().unwrap_err(), PlanetError::Stopped { planet_id: 5 });
+
+        planet.kill().unwrap();
+        planet.join().unwrap();
+    }
+}
@@ -0,0 +1,183 @@
+use crate::handle::{PlanetError, PlanetHandle};
+use crate::report::FinalReport;
+use crate::PlanetConfig;
+use common_game::protocols::orchestrator_planet::{OrchestratorToPlanet, PlanetToOrchestrator};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+/// Outcome of a fleet-wide operation, one entry per planet in launch order.
+pub type FleetResults<T = ()> = Vec<(u32, Result<T, PlanetError>)>;
+
+/// A group of planets, each running on its own thread.
+///
+/// `Planet::run` blocks for the whole life of the planet, so every planet
+/// needs a dedicated thread; see [`PlanetHandle::spawn`].
+///
+/// The coordinated operations ([`start`](Self::start), [`stop`](Self::stop),
+/// [`kill`](Self::kill)) send the command to every planet first and then wait
+/// for each acknowledgement, reporting the outcome per planet.
+#[derive(Debug)]
+pub struct Fleet {
+    planets: Vec<PlanetHandle>,
+    timeout: Duration,
+}
+
+impl Fleet {
+    /// Spawns a planet for each configuration. The planets start stopped,
+    /// waiting for [`start`](Self::start).
+    ///
+    /// # Errors
+    /// Returns an error if two configurations share a planet id, or if a
+    /// planet cannot be spawned; the planets already running are shut down.
+    pub fn launch(configs: impl IntoIterator<Item = PlanetConfig>) -> Result<Self, String> {
+        let configs: Vec<_> = configs.into_iter().collect();
+        let mut ids = HashSet::new();
+        if let Some(config) = configs.iter().find(|c| !ids.insert(c.planet_id)) {
+            return Err(format!("duplicate planet id {}", config.planet_id));
+        }
+
+        let mut fleet = Fleet {
+            planets: Vec::with_capacity(configs.len()),
+            timeout: Duration::from_secs(1),
+        };
+        for config in configs {
+            match PlanetHandle::spawn(config) {
+                Ok(planet) => fleet.planets.push(planet),
+                Err(e) => {
+                    fleet.shutdown();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(fleet)
+    }
+
+    /// How long the coordinated operations wait for each acknowledgement,
+    /// one second by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn planets(&self) -> &[PlanetHandle] {
+        &self.planets
+    }
+
+    pub fn planet(&self, id: u32) -> Option<&PlanetHandle> {
+        self.planets.iter().find(|p| p.id() == id)
+    }
+
+    /// Starts the AI of every planet.
+    pub fn start(&self) -> FleetResults {
+        self.broadcast(|| OrchestratorToPlanet::StartPlanetAI, "StartPlanetAI", |reply| match reply {
+            PlanetToOrchestrator::StartPlanetAIResult { .. } => Ok(()),
+            other => Err(other),
+        })
+    }
+
+    /// Stops the AI of every planet.
+    pub fn stop(&self) -> FleetResults {
+        self.broadcast(|| OrchestratorToPlanet::StopPlanetAI, "StopPlanetAI", |reply| match reply {
+            PlanetToOrchestrator::StopPlanetAIResult { .. } => Ok(()),
+            other => Err(other),
+        })
+    }
+
+    /// Kills every planet, which ends their threads.
+    pub fn kill(&self) -> FleetResults {
+        self.broadcast(|| OrchestratorToPlanet::KillPlanet, "KillPlanet", |reply| match reply {
+            PlanetToOrchestrator::KillPlanetResult { .. } => Ok(()),
+            other => Err(other),
+        })
+    }
+
+    /// Waits for every planet thread and returns its [`FinalReport`].
+    /// Planets that were not killed keep it blocked.
+    pub fn join(mut self) -> FleetResults<FinalReport> {
+        self.planets.iter_mut().map(|p| (p.id(), p.join())).collect()
+    }
+
+    /// Kills every planet and joins their threads.
+    ///
+    /// A planet whose kill fails is not joined, so a stuck planet cannot block
+    /// the shutdown; its entry holds the kill error.
+    pub fn shutdown(mut self) -> FleetResults<FinalReport> {
+        let killed = self.kill();
+        self.planets
+            .iter_mut()
+            .zip(killed)
+            .map(|(planet, (id, kill))| (id, kill.and_then(|()| planet.join())))
+            .collect()
+    }
+
+    fn broadcast(
+        &self,
+        msg: impl Fn() -> OrchestratorToPlanet,
+        request: &'static str,
+        expected: impl Fn(PlanetToOrchestrator) -> Result<(), PlanetToOrchestrator>,
+    ) -> FleetResults {
+        let sent: Vec<_> = self.planets.iter().map(|p| p.send(msg())).collect();
+        let deadline = Instant::now() + self.timeout;
+        self.planets
+            .iter()
+            .zip(sent)
+            .map(|(planet, sent)| {
+                (planet.id(), sent.and_then(|()| planet.reply(deadline, request, &expected)))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RocketStrategy;
+    use common_game::components::resource::BasicResourceType;
+    use common_game::components::sunray::Sunray;
+
+    fn configs(ids: &[u32]) -> Vec<PlanetConfig> {
+        ids.iter()
+            .map(|id| PlanetConfig::new(*id, RocketStrategy::Safe, Some(BasicResourceType::Oxygen)))
+            .collect()
+    }
+
+    fn all_ok<T>(results: &FleetResults<T>) -> bool {
+        results.iter().all(|(_, r)| r.is_ok())
+    }
+
+    #[test]
+    fn test_fleet_lifecycle() {
+        // SCENARIO: Launch three planets, drive one, then stop, kill and join them all.
+        let fleet = Fleet::launch(configs(&[1, 2, 3])).expect("Failed to launch fleet");
+        assert_eq!(fleet.planets().len(), 3);
+
+        assert!(all_ok(&fleet.start()));
+
+        let planet = fleet.planet(2).expect("Planet 2 missing");
+        planet.sender().send(OrchestratorToPlanet::Sunray(Sunray::default())).unwrap();
+        let ack = planet.receiver().recv_timeout(Duration::from_secs(1)).unwrap();
+        assert!(matches!(ack, PlanetToOrchestrator::SunrayAck { planet_id: 2 }));
+
+        assert!(all_ok(&fleet.stop()));
+        assert!(all_ok(&fleet.kill()));
+        let joined = fleet.join();
+        assert_eq!(joined.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert!(all_ok(&joined));
+    }
+
+    #[test]
+    fn test_errors_are_reported_per_planet() {
+        // SCENARIO: Stopping a fleet that was never started fails for every planet.
+        let fleet = Fleet::launch(configs(&[7, 8])).expect("Failed to launch fleet");
+        let stopped = fleet.stop();
+        assert_eq!(stopped[0], (7, Err(PlanetError::Stopped { planet_id: 7 })));
+        assert_eq!(stopped[1], (8, Err(PlanetError::Stopped { planet_id: 8 })));
+        assert!(all_ok(&fleet.shutdown()));
+    }
+
+    #[test]
+    fn test_duplicate_ids_are_rejected() {
+        let err = Fleet::launch(configs(&[4, 5, 4])).unwrap_err();
+        assert!(err.contains("duplicate planet id 4"), "{err}");
+    }
+}
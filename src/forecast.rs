@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+/// Events kept to estimate the arrival rates.
+const WINDOW: usize = 100;
+
+/// Kind of an event handled by the AI, as far as forecasting is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Arrival {
+    Sunray,
+    Asteroid,
+    /// A request from an explorer, the demand the `Planner` strategy weighs.
+    Explorer,
+    Other,
+}
+
+/// The last [`WINDOW`] events handled by the AI.
+#[derive(Debug, Default)]
+pub(crate) struct Arrivals {
+    recent: VecDeque<Arrival>,
+}
+
+impl Arrivals {
+    pub(crate) fn observe(&mut self, arrival: Arrival) {
+        if self.recent.len() == WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(arrival);
+    }
+
+    /// Share of the recent events of this kind, starting from even odds
+    /// between sunrays and asteroids when nothing was seen yet.
+    pub(crate) fn rate(&self, arrival: Arrival) -> f64 {
+        let seen = self.recent.iter().filter(|a| **a == arrival).count() as f64;
+        (seen + 1.0) / (self.recent.len() as f64 + 2.0)
+    }
+}
+
+/// Expected activity over the next `horizon` events, from the arrival rates
+/// of the last 100 events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SunrayForecast {
+    pub horizon: u32,
+    pub sunrays: f64,
+    pub asteroids: f64,
+    /// Charged cells expected at the end of the horizon if nothing is spent,
+    /// at most the number of cells.
+    pub charged_cells: f64,
+}
+
+impl SunrayForecast {
+    pub(crate) fn new(arrivals: &Arrivals, horizon: u32, charged: u32, total: u32) -> Self {
+        let sunrays = arrivals.rate(Arrival::Sunray) * f64::from(horizon);
+        SunrayForecast {
+            horizon,
+            sunrays,
+            asteroids: arrivals.rate(Arrival::Asteroid) * f64::from(horizon),
+            charged_cells: (f64::from(charged) + sunrays).min(f64::from(total)),
+        }
+    }
+}
+
+/// Limits the cells sold to explorers in each period of `period` events.
+///
+/// At the start of a period the budget is the energy expected to be available
+/// during it (the charged cells plus the forecast sunrays) minus a cell for
+/// every forecast asteroid, and one more if a rocket-building planet has no
+/// rocket ready. Cells reserved by an explorer are still handed to it, but
+/// count against the budget.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EnergyBudget {
+    pub period: u32,
+}
+
+impl Default for EnergyBudget {
+    fn default() -> Self {
+        EnergyBudget { period: 20 }
+    }
+}
+
+impl EnergyBudget {
+    /// Cells that can be sold in a period starting with this forecast;
+    /// `rocket_cells` is what rocket readiness needs besides the asteroids,
+    /// which only count when the planet builds rockets to meet them.
+    pub(crate) fn cells(
+        &self,
+        forecast: &SunrayForecast,
+        charged: u32,
+        needed_for_asteroids: bool,
+        rocket_cells: u32,
+    ) -> u32 {
+        let energy = f64::from(charged) + forecast.sunrays;
+        let asteroids = if needed_for_asteroids { forecast.asteroids.ceil() } else { 0.0 };
+        let needed = asteroids + f64::from(rocket_cells);
+        (energy - needed).max(0.0).floor() as u32
+    }
+}
+
+/// The budget of the current period.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PeriodBudget {
+    pub(crate) ends_at: u64,
+    pub(crate) cells: u32,
+    pub(crate) sold: u32,
+}
+
+impl PeriodBudget {
+    pub(crate) fn left(&self) -> u32 {
+        self.cells.saturating_sub(self.sold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forecast_follows_recent_arrivals() {
+        let mut arrivals = Arrivals::default();
+        let empty = SunrayForecast::new(&arrivals, 10, 0, 5);
+        assert_eq!(empty.sunrays, 5.0);
+        assert_eq!(empty.asteroids, 5.0);
+
+        // 98 sunrays and no asteroid: (98 + 1) / (98 + 2) sunrays per event
+        for _ in 0..98 {
+            arrivals.observe(Arrival::Sunray);
+        }
+        let forecast = SunrayForecast::new(&arrivals, 100, 2, 5);
+        assert_eq!(forecast.sunrays, 99.0);
+        assert_eq!(forecast.asteroids, 1.0);
+        assert_eq!(forecast.charged_cells, 5.0, "Capped by the number of cells");
+
+        // Older events leave the window
+        for _ in 0..WINDOW {
+            arrivals.observe(Arrival::Other);
+        }
+        assert!(SunrayForecast::new(&arrivals, 10, 0, 5).sunrays < 0.1);
+    }
+
+    #[test]
+    fn test_budget_keeps_energy_for_rockets() {
+        let budget = EnergyBudget { period: 10 };
+        let forecast = SunrayForecast { horizon: 10, sunrays: 4.5, asteroids: 1.2, charged_cells: 5.0 };
+        // 2 + 4.5 energy, 2 asteroids, 1 rocket to rebuild
+        assert_eq!(budget.cells(&forecast, 2, true, 1), 3);
+        assert_eq!(budget.cells(&forecast, 0, true, 5), 0);
+        // No rockets, nothing to keep for the asteroids
+        assert_eq!(budget.cells(&forecast, 2, false, 0), 6);
+    }
+}
@@ -0,0 +1,333 @@
+//! Fuzzing entry point for explorer message handling.
+//!
+//! [`drive_explorer_messages`] decodes arbitrary bytes into a sequence of
+//! orchestrator and explorer messages and plays them against a real planet
+//! running on its own thread. Any panic in the AI is re-raised on the calling
+//! thread, so libFuzzer (see `fuzz/`) reports it as a crash.
+//!
+//! Input layout: byte 0 picks the [`RocketStrategy`], byte 1 the basic
+//! resource, byte 2 the reservation ttl (`0` disables reservations); every
+//! following op is one tag byte plus its arguments:
+//!
+//! | tag % 8 | message                          | arguments             |
+//! |---------|----------------------------------|-----------------------|
+//! | 0       | `Sunray`                         |                       |
+//! | 1       | `Asteroid`                       |                       |
+//! | 2       | `InternalStateRequest`           |                       |
+//! | 3       | `SupportedResourceRequest`       | explorer              |
+//! | 4       | `SupportedCombinationRequest`    | explorer              |
+//! | 5       | `GenerateResourceRequest`        | explorer, resource    |
+//! | 6       | `CombineResourceRequest`         | explorer, combination |
+//! | 7       | `AvailableEnergyCellRequest`     | explorer              |
+//!
+//! Missing trailing bytes read as `0`.
+
+use crate::{create_planet, PlanetConfig, PlanetCoreThinkingModel, ReservationPolicy, RocketStrategy};
+use common_game::components::asteroid::Asteroid;
+use common_game::components::energy_cell::EnergyCell;
+use common_game::components::planet::{Planet, PlanetType};
+use common_game::components::resource::{
+    BasicResourceType, Carbon, Combinator, ComplexResourceRequest, ComplexResourceType, Diamond,
+    Generator, Hydrogen, Life, Oxygen, Robot, Silicon, Water,
+};
+use common_game::components::sunray::Sunray;
+use common_game::protocols::orchestrator_planet::{OrchestratorToPlanet, PlanetToOrchestrator};
+use common_game::protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer};
+use crossbeam_channel::{unbounded, Receiver, Sender};
+use std::collections::HashMap;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const FUZZ_PLANET_ID: u32 = 1;
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Explorer ids are folded into a small range so that sequences revisit them.
+const EXPLORERS: u8 = 4;
+
+const BASIC_RESOURCES: [BasicResourceType; 4] = [
+    BasicResourceType::Oxygen,
+    BasicResourceType::Hydrogen,
+    BasicResourceType::Carbon,
+    BasicResourceType::Silicon,
+];
+
+/// Plays the message sequence encoded in `data` against a freshly built planet.
+///
+/// # Panics
+/// Panics if the planet panics, stops answering, or exits with an error.
+pub fn drive_explorer_messages(data: &[u8]) {
+    let mut bytes = data.iter().copied().peekable();
+
+    let strategy = RocketStrategy::ALL[next(&mut bytes) as usize % RocketStrategy::ALL.len()].clone();
+    let resource = BASIC_RESOURCES[(next(&mut bytes) % 4) as usize];
+    let mut config = PlanetConfig::new(FUZZ_PLANET_ID, strategy, Some(resource));
+    let ttl = next(&mut bytes);
+    if ttl > 0 {
+        config.reservation = Some(ReservationPolicy {
+            ttl: ttl as u64,
+            ..ReservationPolicy::default()
+        });
+    }
+
+    let mint = ResourceMint::new();
+    let mut harness = Harness::start(config);
+    while bytes.peek().is_some() {
+        match next(&mut bytes) % 8 {
+            0 => harness.orchestrator(OrchestratorToPlanet::Sunray(Sunray::default())),
+            1 => harness.orchestrator(OrchestratorToPlanet::Asteroid(Asteroid::default())),
+            2 => harness.orchestrator(OrchestratorToPlanet::InternalStateRequest),
+            3 => {
+                let explorer_id = (next(&mut bytes) % EXPLORERS) as u32;
+                harness.explorer(ExplorerToPlanet::SupportedResourceRequest { explorer_id });
+            }
+            4 => {
+                let explorer_id = (next(&mut bytes) % EXPLORERS) as u32;
+                harness.explorer(ExplorerToPlanet::SupportedCombinationRequest { explorer_id });
+            }
+            5 => {
+                let explorer_id = (next(&mut bytes) % EXPLORERS) as u32;
+                let resource = BASIC_RESOURCES[(next(&mut bytes) % 4) as usize];
+                harness.explorer(ExplorerToPlanet::GenerateResourceRequest { explorer_id, resource });
+            }
+            6 => {
+                let explorer_id = (next(&mut bytes) % EXPLORERS) as u32;
+                let msg = mint.request(next(&mut bytes));
+                harness.explorer(ExplorerToPlanet::CombineResourceRequest { explorer_id, msg });
+            }
+            _ => {
+                let explorer_id = (next(&mut bytes) % EXPLORERS) as u32;
+                harness.explorer(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id });
+            }
+        }
+    }
+    harness.kill();
+}
+
+/// A planet running on its own thread, driven one message at a time.
+struct Harness {
+    to_planet: Sender<OrchestratorToPlanet>,
+    from_planet: Receiver<PlanetToOrchestrator>,
+    to_planet_explorer: Sender<ExplorerToPlanet>,
+    explorers: HashMap<u32, Receiver<PlanetToExplorer>>,
+    handle: Option<JoinHandle<Result<(), String>>>,
+}
+
+impl Harness {
+    fn start(config: PlanetConfig) -> Self {
+        let (to_planet, rx_orchestrator) = unbounded();
+        let (tx_orchestrator, from_planet) = unbounded();
+        let (to_planet_explorer, rx_explorer) = unbounded();
+        let mut planet = create_planet(config, rx_orchestrator, tx_orchestrator, rx_explorer)
+            .expect("Failed to create planet");
+        let handle = std::thread::spawn(move || planet.run());
+
+        let mut harness = Harness {
+            to_planet,
+            from_planet,
+            to_planet_explorer,
+            explorers: HashMap::new(),
+            handle: Some(handle),
+        };
+        harness.orchestrator(OrchestratorToPlanet::StartPlanetAI);
+        harness
+    }
+
+    /// Sends an orchestrator message and waits for its reply.
+    fn orchestrator(&mut self, msg: OrchestratorToPlanet) {
+        if self.to_planet.send(msg).is_err() {
+            self.fail("planet disconnected");
+        }
+        if self.from_planet.recv_timeout(REPLY_TIMEOUT).is_err() {
+            self.fail("planet did not answer the orchestrator");
+        }
+    }
+
+    /// Sends an explorer message, landing the explorer first if needed.
+    ///
+    /// Some requests legitimately get no reply, so each one is followed by a
+    /// `SupportedCombinationRequest`, which always does: once its reply is in,
+    /// the message under test has been handled.
+    fn explorer(&mut self, msg: ExplorerToPlanet) {
+        let explorer_id = msg.explorer_id();
+        if !self.explorers.contains_key(&explorer_id) {
+            let (new_sender, responses) = unbounded();
+            self.orchestrator(OrchestratorToPlanet::IncomingExplorerRequest {
+                explorer_id,
+                new_sender,
+            });
+            self.explorers.insert(explorer_id, responses);
+        }
+
+        let mut barriers = 1;
+        if matches!(msg, ExplorerToPlanet::SupportedCombinationRequest { .. }) {
+            barriers += 1;
+        }
+        let sent = self.to_planet_explorer.send(msg).is_ok()
+            && self
+                .to_planet_explorer
+                .send(ExplorerToPlanet::SupportedCombinationRequest { explorer_id })
+                .is_ok();
+        if !sent {
+            self.fail("planet disconnected");
+        }
+
+        while barriers > 0 {
+            match self.explorers[&explorer_id].recv_timeout(REPLY_TIMEOUT) {
+                Ok(PlanetToExplorer::SupportedCombinationResponse { .. }) => barriers -= 1,
+                Ok(_) => {}
+                Err(_) => self.fail("planet did not answer the explorer"),
+            }
+        }
+    }
+
+    fn kill(mut self) {
+        self.orchestrator(OrchestratorToPlanet::KillPlanet);
+        if let Err(e) = self.join() {
+            panic!("planet exited with an error: {e}");
+        }
+    }
+
+    /// Joins the planet thread, re-raising its panic if it had one.
+    fn join(&mut self) -> Result<(), String> {
+        match self.handle.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(panic)) => std::panic::resume_unwind(panic),
+            None => Ok(()),
+        }
+    }
+
+    fn fail(&mut self, reason: &str) -> ! {
+        // Dropping the sender lets a healthy planet thread exit.
+        let (to_planet, _) = unbounded();
+        drop(std::mem::replace(&mut self.to_planet, to_planet));
+        let _ = self.join();
+        panic!("{reason}");
+    }
+}
+
+/// Produces the resources needed to build arbitrary `ComplexResourceRequest`s.
+///
+/// Resources can only be made by a generator or combinator, and those only by
+/// `Planet::new`, so the mint keeps two planets that are never run.
+struct ResourceMint {
+    generator_planet: Planet,
+    combinator_planet: Planet,
+}
+
+impl ResourceMint {
+    fn new() -> Self {
+        let planet = |planet_type, gen_rules: Vec<BasicResourceType>, comb_rules| {
+            let (_, rx_orchestrator) = unbounded();
+            let (tx_orchestrator, _) = unbounded();
+            let (_, rx_explorer) = unbounded();
+            let config = PlanetConfig::new(FUZZ_PLANET_ID, RocketStrategy::Disabled, None);
+            Planet::new(
+                FUZZ_PLANET_ID,
+                planet_type,
+                Box::new(PlanetCoreThinkingModel::new(&config)),
+                gen_rules,
+                comb_rules,
+                (rx_orchestrator, tx_orchestrator),
+                rx_explorer,
+            )
+            .expect("Failed to create mint planet")
+        };
+        ResourceMint {
+            generator_planet: planet(PlanetType::D, BASIC_RESOURCES.to_vec(), vec![]),
+            combinator_planet: planet(
+                PlanetType::C,
+                vec![BasicResourceType::Oxygen],
+                vec![
+                    ComplexResourceType::Diamond,
+                    ComplexResourceType::Water,
+                    ComplexResourceType::Life,
+                    ComplexResourceType::Robot,
+                    ComplexResourceType::Dolphin,
+                    ComplexResourceType::AIPartner,
+                ],
+            ),
+        }
+    }
+
+    fn request(&self, kind: u8) -> ComplexResourceRequest {
+        match kind % 6 {
+            0 => ComplexResourceRequest::Water(self.hydrogen(), self.oxygen()),
+            1 => ComplexResourceRequest::Diamond(self.carbon(), self.carbon()),
+            2 => ComplexResourceRequest::Life(self.water(), self.carbon()),
+            3 => ComplexResourceRequest::Robot(self.silicon(), self.life()),
+            4 => ComplexResourceRequest::Dolphin(self.water(), self.life()),
+            _ => ComplexResourceRequest::AIPartner(self.robot(), self.diamond()),
+        }
+    }
+
+    fn generator(&self) -> &Generator {
+        self.generator_planet.generator()
+    }
+
+    fn combinator(&self) -> &Combinator {
+        self.combinator_planet.combinator()
+    }
+
+    fn oxygen(&self) -> Oxygen {
+        self.generator().make_oxygen(&mut charged_cell()).expect("mint: oxygen")
+    }
+
+    fn hydrogen(&self) -> Hydrogen {
+        self.generator().make_hydrogen(&mut charged_cell()).expect("mint: hydrogen")
+    }
+
+    fn carbon(&self) -> Carbon {
+        self.generator().make_carbon(&mut charged_cell()).expect("mint: carbon")
+    }
+
+    fn silicon(&self) -> Silicon {
+        self.generator().make_silicon(&mut charged_cell()).expect("mint: silicon")
+    }
+
+    fn water(&self) -> Water {
+        let made = self.combinator().make_water(self.hydrogen(), self.oxygen(), &mut charged_cell());
+        made.map_err(|(e, ..)| e).expect("mint: water")
+    }
+
+    fn diamond(&self) -> Diamond {
+        let made = self.combinator().make_diamond(self.carbon(), self.carbon(), &mut charged_cell());
+        made.map_err(|(e, ..)| e).expect("mint: diamond")
+    }
+
+    fn life(&self) -> Life {
+        let made = self.combinator().make_life(self.water(), self.carbon(), &mut charged_cell());
+        made.map_err(|(e, ..)| e).expect("mint: life")
+    }
+
+    fn robot(&self) -> Robot {
+        let made = self.combinator().make_robot(self.silicon(), self.life(), &mut charged_cell());
+        made.map_err(|(e, ..)| e).expect("mint: robot")
+    }
+}
+
+fn next(bytes: &mut impl Iterator<Item = u8>) -> u8 {
+    bytes.next().unwrap_or(0)
+}
+
+fn charged_cell() -> EnergyCell {
+    let mut cell = EnergyCell::new();
+    cell.charge(Sunray::default());
+    cell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_seed_corpus_runs_clean() {
+        let corpus = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/corpus/explorer_msg");
+        let mut seeds = 0;
+        for entry in std::fs::read_dir(corpus).expect("missing seed corpus") {
+            let data = std::fs::read(entry.unwrap().path()).unwrap();
+            drive_explorer_messages(&data);
+            seeds += 1;
+        }
+        assert!(seeds > 0, "seed corpus is empty");
+    }
+}
@@ -0,0 +1,75 @@
+use common_game::components::energy_cell::EnergyCell;
+use common_game::components::resource::{BasicResource, BasicResourceType, Generator};
+
+/// Generator call producing one basic resource from a charged cell.
+pub(crate) type Make = fn(&Generator, &mut EnergyCell) -> Result<BasicResource, String>;
+
+/// The generator call for every basic resource type.
+pub(crate) fn make_fn(resource: BasicResourceType) -> Make {
+    match resource {
+        BasicResourceType::Oxygen => |g, cell| g.make_oxygen(cell).map(BasicResource::Oxygen),
+        BasicResourceType::Hydrogen => |g, cell| g.make_hydrogen(cell).map(BasicResource::Hydrogen),
+        BasicResourceType::Carbon => |g, cell| g.make_carbon(cell).map(BasicResource::Carbon),
+        BasicResourceType::Silicon => |g, cell| g.make_silicon(cell).map(BasicResource::Silicon),
+    }
+}
+
+/// Generates `resource` from `cell`, `None` when the generator has no recipe
+/// for it or the cell is not charged.
+pub(crate) fn make(generator: &Generator, resource: BasicResourceType, cell: &mut EnergyCell) -> Option<BasicResource> {
+    make_fn(resource)(generator, cell).ok()
+}
+
+/// Why [`PlanetCoreThinkingModel::generate_batch`](crate::PlanetCoreThinkingModel::generate_batch)
+/// or [`ExplorerClient::generate_batch`](crate::ExplorerClient::generate_batch) stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchStop {
+    /// Every unit asked for was generated.
+    Completed,
+    /// The explorer policy rejected this explorer when it landed.
+    Rejected,
+    /// The service tier does not serve this explorer.
+    NotServed,
+    /// The cells of the current energy budget period are all sold.
+    BudgetExhausted,
+    /// No charged cell is left for this explorer: the others are reserved,
+    /// kept for rockets or empty.
+    NoFreeCell,
+    /// The planet does not generate this resource.
+    Unsupported,
+    /// A cell was picked but the generator produced nothing.
+    GenerationFailed,
+    /// The planet refused a unit although cells were available to the
+    /// explorer, because of its service tier or energy budget; a refusal
+    /// over the explorer protocol carries no reason.
+    Refused,
+}
+
+/// The outcome of a batch: the units generated, in order, and why it stopped.
+#[derive(Debug)]
+pub struct GeneratedBatch {
+    pub resources: Vec<BasicResource>,
+    pub stop: BatchStop,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_game::components::sunray::Sunray;
+
+    #[test]
+    fn test_no_recipe_keeps_the_cell() {
+        let generator = Generator::new();
+        let mut cell = EnergyCell::new();
+        cell.charge(Sunray::default());
+        for resource in [
+            BasicResourceType::Oxygen,
+            BasicResourceType::Hydrogen,
+            BasicResourceType::Carbon,
+            BasicResourceType::Silicon,
+        ] {
+            assert!(make(&generator, resource, &mut cell).is_none());
+            assert!(cell.is_charged(), "{resource:?} spent the cell");
+        }
+    }
+}
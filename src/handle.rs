@@ -0,0 +1,405 @@
+use crate::admission::Rejections;
+use crate::report::FinalReport;
+use crate::{create_planet_with_reporter, PlanetConfig};
+use common_game::components::asteroid::Asteroid;
+use common_game::components::planet::DummyPlanetState;
+use common_game::components::rocket::Rocket;
+use common_game::components::sunray::Sunray;
+use common_game::protocols::orchestrator_planet::{
+    OrchestratorToPlanet, PlanetToOrchestrator, PlanetToOrchestratorKind,
+};
+use common_game::protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer, PlanetToExplorerKind};
+use crossbeam_channel::{unbounded, Receiver, RecvTimeoutError, Sender};
+use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Error returned by the [`PlanetHandle`] requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanetError {
+    /// The planet channels are closed: its thread has ended.
+    Disconnected { planet_id: u32 },
+    /// No reply arrived within the handle timeout.
+    Timeout { planet_id: u32, request: &'static str },
+    /// The planet AI is stopped and answered `Stopped`.
+    Stopped { planet_id: u32 },
+    /// The planet answered with a message that does not match the request.
+    UnexpectedReply {
+        planet_id: u32,
+        request: &'static str,
+        reply: PlanetToOrchestratorKind,
+    },
+    /// The planet answered an explorer request with a message that does not
+    /// match it.
+    UnexpectedExplorerReply {
+        planet_id: u32,
+        request: &'static str,
+        reply: PlanetToExplorerKind,
+    },
+    /// The planet refused the request with the given reason.
+    Rejected { planet_id: u32, reason: String },
+    /// `Planet::run` returned an error, or the planet thread panicked.
+    Run { planet_id: u32, reason: String },
+    /// The planet thread has already been joined.
+    AlreadyJoined { planet_id: u32 },
+}
+
+impl Display for PlanetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanetError::Disconnected { planet_id } => write!(f, "planet {planet_id} is disconnected"),
+            PlanetError::Timeout { planet_id, request } => {
+                write!(f, "planet {planet_id} did not answer {request} in time")
+            }
+            PlanetError::Stopped { planet_id } => write!(f, "planet {planet_id} is stopped"),
+            PlanetError::UnexpectedReply { planet_id, request, reply } => {
+                write!(f, "planet {planet_id} answered {request} with {reply:?}")
+            }
+            PlanetError::UnexpectedExplorerReply { planet_id, request, reply } => {
+                write!(f, "planet {planet_id} answered explorer {request} with {reply:?}")
+            }
+            PlanetError::Rejected { planet_id, reason } => {
+                write!(f, "planet {planet_id} rejected the request: {reason}")
+            }
+            PlanetError::Run { planet_id, reason } => write!(f, "planet {planet_id} failed: {reason}"),
+            PlanetError::AlreadyJoined { planet_id } => {
+                write!(f, "planet {planet_id} was already joined")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PlanetError {}
+
+/// The orchestrator side of a running planet: its channels, the thread running
+/// `Planet::run`, and typed helpers for the request/response pairs.
+///
+/// The helpers wait at most the handle timeout (one second by default) for the
+/// reply, and expect it to be the next message on the orchestrator channel:
+/// a leftover reply from a raw [`sender`](Self::sender) call is reported as
+/// [`PlanetError::UnexpectedReply`]. Replies that arrive after their request
+/// timed out are skipped by the next helpers.
+#[derive(Debug)]
+pub struct PlanetHandle {
+    id: u32,
+    to_planet: Sender<OrchestratorToPlanet>,
+    from_planet: Receiver<PlanetToOrchestrator>,
+    explorer_sender: Sender<ExplorerToPlanet>,
+    thread: Option<JoinHandle<(Result<(), String>, FinalReport)>>,
+    timeout: Duration,
+    /// Replies still owed for requests that timed out.
+    late: AtomicU32,
+    /// Explorers the planet AI rejected, see [`incoming_explorer`](Self::incoming_explorer).
+    rejections: Rejections,
+}
+
+impl PlanetHandle {
+    /// Creates the planet and runs it on a thread named `planet-<id>`. The
+    /// planet starts stopped, waiting for [`start`](Self::start).
+    ///
+    /// # Errors
+    /// Returns an error if the planet cannot be created or the thread cannot
+    /// be spawned.
+    pub fn spawn(config: PlanetConfig) -> Result<Self, String> {
+        let id = config.planet_id;
+        let (to_planet, orchestrator_rx) = unbounded();
+        let (orchestrator_tx, from_planet) = unbounded();
+        let (explorer_sender, explorer_rx) = unbounded();
+        let (mut planet, reporter) =
+            create_planet_with_reporter(config, orchestrator_rx, orchestrator_tx, explorer_rx)
+                .map_err(|e| format!("planet {id}: {e}"))?;
+        let rejections = reporter.rejections.clone();
+        let thread = thread::Builder::new()
+            .name(format!("planet-{id}"))
+            .spawn(move || {
+                let result = planet.run();
+                (result, reporter.finish(planet.state()))
+            })
+            .map_err(|e| format!("planet {id}: cannot spawn thread: {e}"))?;
+        Ok(PlanetHandle {
+            id,
+            to_planet,
+            from_planet,
+            explorer_sender,
+            thread: Some(thread),
+            timeout: Duration::from_secs(1),
+            late: AtomicU32::new(0),
+            rejections,
+        })
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Sender for raw orchestrator messages to the planet.
+    pub fn sender(&self) -> &Sender<OrchestratorToPlanet> {
+        &self.to_planet
+    }
+
+    /// Receiver for the raw planet responses to the orchestrator.
+    pub fn receiver(&self) -> &Receiver<PlanetToOrchestrator> {
+        &self.from_planet
+    }
+
+    /// Sender to hand to the explorers that land on this planet.
+    pub fn explorer_sender(&self) -> &Sender<ExplorerToPlanet> {
+        &self.explorer_sender
+    }
+
+    pub fn start(&self) -> Result<(), PlanetError> {
+        self.request(OrchestratorToPlanet::StartPlanetAI, "StartPlanetAI", |reply| match reply {
+            PlanetToOrchestrator::StartPlanetAIResult { .. } => Ok(()),
+            other => Err(other),
+        })
+    }
+
+    /// Stops the AI; a stopped planet answers `Stopped` to everything but
+    /// `StartPlanetAI` and `KillPlanet`.
+    pub fn stop(&self) -> Result<(), PlanetError> {
+        self.request(OrchestratorToPlanet::StopPlanetAI, "StopPlanetAI", |reply| match reply {
+            PlanetToOrchestrator::StopPlanetAIResult { .. } => Ok(()),
+            other => Err(other),
+        })
+    }
+
+    /// Kills the planet, which ends its thread.
+    pub fn kill(&self) -> Result<(), PlanetError> {
+        self.request(OrchestratorToPlanet::KillPlanet, "KillPlanet", |reply| match reply {
+            PlanetToOrchestrator::KillPlanetResult { .. } => Ok(()),
+            other => Err(other),
+        })
+    }
+
+    pub fn send_sunray(&self, sunray: Sunray) -> Result<(), PlanetError> {
+        self.request(OrchestratorToPlanet::Sunray(sunray), "Sunray", |reply| match reply {
+            PlanetToOrchestrator::SunrayAck { .. } => Ok(()),
+            other => Err(other),
+        })
+    }
+
+    /// Returns the rocket the planet defends itself with, if any.
+    pub fn send_asteroid(&self, asteroid: Asteroid) -> Result<Option<Rocket>, PlanetError> {
+        self.request(OrchestratorToPlanet::Asteroid(asteroid), "Asteroid", |reply| match reply {
+            PlanetToOrchestrator::AsteroidAck { rocket, .. } => Ok(rocket),
+            other => Err(other),
+        })
+    }
+
+    pub fn internal_state(&self) -> Result<DummyPlanetState, PlanetError> {
+        self.request(OrchestratorToPlanet::InternalStateRequest, "InternalStateRequest", |reply| {
+            match reply {
+                PlanetToOrchestrator::InternalStateResponse { planet_state, .. } => Ok(planet_state),
+                other => Err(other),
+            }
+        })
+    }
+
+    /// Tells the planet an explorer has landed, with the channel to answer it on.
+    ///
+    /// The runtime accepts every landing, so the handle also asks the planet
+    /// AI: an explorer its [`ExplorerPolicy`](crate::ExplorerPolicy) rejected
+    /// is sent away again, and the call fails with [`PlanetError::Rejected`].
+    pub fn incoming_explorer(
+        &self,
+        explorer_id: u32,
+        new_sender: Sender<PlanetToExplorer>,
+    ) -> Result<(), PlanetError> {
+        let msg = OrchestratorToPlanet::IncomingExplorerRequest { explorer_id, new_sender };
+        let res = self.request(msg, "IncomingExplorerRequest", |reply| match reply {
+            PlanetToOrchestrator::IncomingExplorerResponse { res, .. } => Ok(res),
+            other => Err(other),
+        })?;
+        res.map_err(|reason| PlanetError::Rejected { planet_id: self.id, reason })?;
+        if let Some(reason) = self.rejections.reason(explorer_id) {
+            self.outgoing_explorer(explorer_id)?;
+            return Err(PlanetError::Rejected { planet_id: self.id, reason });
+        }
+        Ok(())
+    }
+
+    /// Tells the planet an explorer has left; it stops answering it.
+    pub fn outgoing_explorer(&self, explorer_id: u32) -> Result<(), PlanetError> {
+        let msg = OrchestratorToPlanet::OutgoingExplorerRequest { explorer_id };
+        let res = self.request(msg, "OutgoingExplorerRequest", |reply| match reply {
+            PlanetToOrchestrator::OutgoingExplorerResponse { res, .. } => Ok(res),
+            other => Err(other),
+        })?;
+        res.map_err(|reason| PlanetError::Rejected { planet_id: self.id, reason })
+    }
+
+    /// Joins the planet thread, returning the [`FinalReport`] of the planet, or
+    /// the error `Planet::run` returned (the report is logged either way).
+    /// Blocks until the planet is killed or its orchestrator channel closes.
+    pub fn join(&mut self) -> Result<FinalReport, PlanetError> {
+        let planet_id = self.id;
+        let thread = self.thread.take().ok_or(PlanetError::AlreadyJoined { planet_id })?;
+        match thread.join() {
+            Ok((result, report)) => result
+                .map(|()| report)
+                .map_err(|reason| PlanetError::Run { planet_id, reason }),
+            Err(_) => Err(PlanetError::Run {
+                planet_id,
+                reason: "thread panicked".to_string(),
+            }),
+        }
+    }
+
+    fn request<T>(
+        &self,
+        msg: OrchestratorToPlanet,
+        request: &'static str,
+        expected: impl Fn(PlanetToOrchestrator) -> Result<T, PlanetToOrchestrator>,
+    ) -> Result<T, PlanetError> {
+        self.send(msg)?;
+        self.reply(Instant::now() + self.timeout, request, expected)
+    }
+
+    pub(crate) fn send(&self, msg: OrchestratorToPlanet) -> Result<(), PlanetError> {
+        self.to_planet
+            .send(msg)
+            .map_err(|_| PlanetError::Disconnected { planet_id: self.id })
+    }
+
+    /// Waits until `deadline` for the reply to `request`; `expected` extracts
+    /// the value from it or gives back a message of the wrong kind.
+    ///
+    /// While replies to timed-out requests are still owed, a message of the
+    /// wrong kind is taken for one of them and skipped. A reply of the right
+    /// kind is taken either way: if it was a late one, the reply to this
+    /// request is now the one owed.
+    pub(crate) fn reply<T>(
+        &self,
+        deadline: Instant,
+        request: &'static str,
+        expected: impl Fn(PlanetToOrchestrator) -> Result<T, PlanetToOrchestrator>,
+    ) -> Result<T, PlanetError> {
+        let planet_id = self.id;
+        loop {
+            let reply = self.from_planet.recv_deadline(deadline).map_err(|e| match e {
+                RecvTimeoutError::Timeout => {
+                    self.late.fetch_add(1, Ordering::Relaxed);
+                    PlanetError::Timeout { planet_id, request }
+                }
+                RecvTimeoutError::Disconnected => PlanetError::Disconnected { planet_id },
+            })?;
+            match expected(reply) {
+                Ok(value) => return Ok(value),
+                Err(PlanetToOrchestrator::Stopped { .. }) => return Err(PlanetError::Stopped { planet_id }),
+                Err(other) => {
+                    let owed = self.late.load(Ordering::Relaxed);
+                    if owed > 0 {
+                        self.late.store(owed - 1, Ordering::Relaxed);
+                        continue;
+                    }
+                    return Err(PlanetError::UnexpectedReply {
+                        planet_id,
+                        request,
+                        reply: PlanetToOrchestratorKind::from(&other),
+                    });
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CallbackSink, RocketStrategy};
+    use common_game::components::resource::BasicResourceType;
+    use std::sync::atomic::AtomicBool;
+
+    fn spawn(strategy: RocketStrategy) -> PlanetHandle {
+        PlanetHandle::spawn(PlanetConfig::new(3, strategy, Some(BasicResourceType::Carbon)))
+            .expect("Failed to spawn planet")
+    }
+
+    #[test]
+    fn test_typed_requests() {
+        // SCENARIO: A Safe planet charged once defends itself through the typed helpers.
+        let mut planet = spawn(RocketStrategy::Safe);
+        planet.start().unwrap();
+
+        planet.send_sunray(Sunray::default()).unwrap();
+        assert!(planet.internal_state().unwrap().has_rocket);
+        assert!(planet.send_asteroid(Asteroid::default()).unwrap().is_some());
+        assert!(planet.send_asteroid(Asteroid::default()).unwrap().is_none());
+
+        planet.kill().unwrap();
+        let report = planet.join().unwrap();
+        assert_eq!((report.sunrays, report.asteroids, report.asteroids_deflected), (1, 2, 1));
+        assert_eq!(report.rockets_built, 1);
+        assert!(!report.has_rocket);
+        assert_eq!(planet.join(), Err(PlanetError::AlreadyJoined { planet_id: 3 }));
+    }
+
+    #[test]
+    fn test_stopped_and_disconnected_are_reported() {
+        let mut planet = spawn(RocketStrategy::Default);
+
+        // Planets start stopped
+        assert_eq!(planet.internal_state().unwrap_err(), PlanetError::Stopped { planet_id: 3 });
+
+        planet.kill().unwrap();
+        planet.join().unwrap();
+        assert_eq!(
+            planet.send_sunray(Sunray::default()).unwrap_err(),
+            PlanetError::Disconnected { planet_id: 3 }
+        );
+    }
+
+    #[test]
+    fn test_late_reply_is_skipped() {
+        // SCENARIO: The planet answers the first sunray after the timeout; the
+        // late ack does not get in the way of the next requests.
+        let slow = AtomicBool::new(true);
+        let sink = CallbackSink::new(move |event| {
+            if event.payload.get("type").is_some_and(|t| t == "SunrayAck") && slow.swap(false, Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(300));
+            }
+        });
+        let config = PlanetConfig::new(3, RocketStrategy::Default, Some(BasicResourceType::Carbon)).with_log_sink(sink);
+        let mut planet = PlanetHandle::spawn(config).unwrap().with_timeout(Duration::from_millis(100));
+        planet.start().unwrap();
+
+        assert_eq!(
+            planet.send_sunray(Sunray::default()).unwrap_err(),
+            PlanetError::Timeout { planet_id: 3, request: "Sunray" }
+        );
+        thread::sleep(Duration::from_millis(400));
+        assert_eq!(planet.internal_state().unwrap().charged_cells_count, 1);
+        planet.send_sunray(Sunray::default()).unwrap();
+        assert_eq!(planet.internal_state().unwrap().charged_cells_count, 2);
+
+        planet.kill().unwrap();
+        planet.join().unwrap();
+    }
+
+    #[test]
+    fn test_unexpected_reply() {
+        let mut planet = spawn(RocketStrategy::Default);
+        planet.start().unwrap();
+
+        // A raw request whose reply is never read is in the way of the next helper
+        planet.sender().send(OrchestratorToPlanet::InternalStateRequest).unwrap();
+        assert_eq!(
+            planet.send_sunray(Sunray::default()).unwrap_err(),
+            PlanetError::UnexpectedReply {
+                planet_id: 3,
+                request: "Sunray",
+                reply: PlanetToOrchestratorKind::InternalStateResponse,
+            }
+        );
+
+        planet.receiver().recv().unwrap();
+        planet.kill().unwrap();
+        planet.join().unwrap();
+    }
+}
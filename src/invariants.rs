@@ -0,0 +1,169 @@
+//! Property tests for `PlanetCoreThinkingModel`: random interleavings of
+//! orchestrator and explorer messages, checked against a simulated planet.
+
+use crate::{CellSelection, PlanetCells, PlanetConfig, ReservationPolicy, RocketStrategy, SimulatedPlanet};
+use common_game::components::resource::BasicResourceType;
+use common_game::protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer};
+use proptest::prelude::*;
+
+#[derive(Debug, Clone)]
+enum Event {
+    Sunray,
+    Asteroid,
+    StateRequest,
+    SupportedResources(u32),
+    SupportedCombinations(u32),
+    Generate(u32, BasicResourceType),
+    AvailableCells(u32),
+}
+
+fn strategy() -> impl Strategy<Value = RocketStrategy> {
+    prop_oneof![
+        Just(RocketStrategy::Disabled),
+        Just(RocketStrategy::Default),
+        Just(RocketStrategy::Safe),
+        Just(RocketStrategy::EmergencyReserve),
+        Just(RocketStrategy::Stockpile),
+        Just(RocketStrategy::Planner),
+    ]
+}
+
+fn resource() -> impl Strategy<Value = BasicResourceType> {
+    prop_oneof![
+        Just(BasicResourceType::Oxygen),
+        Just(BasicResourceType::Hydrogen),
+        Just(BasicResourceType::Carbon),
+        Just(BasicResourceType::Silicon),
+    ]
+}
+
+fn cell_selection() -> impl Strategy<Value = CellSelection> {
+    prop_oneof![
+        Just(CellSelection::FirstFull),
+        Just(CellSelection::LastFull),
+        Just(CellSelection::RoundRobin),
+        (0usize..3).prop_map(|cells| CellSelection::LowestForRockets { cells }),
+    ]
+}
+
+fn reservation() -> impl Strategy<Value = Option<ReservationPolicy>> {
+    proptest::option::of((1u64..6, 1u32..4).prop_map(|(ttl, max_cells)| ReservationPolicy { ttl, max_cells }))
+}
+
+fn event() -> impl Strategy<Value = Event> {
+    // Explorer ids are drawn from a small range so they collide often.
+    let explorer = 0u32..3;
+    prop_oneof![
+        4 => Just(Event::Sunray),
+        1 => Just(Event::Asteroid),
+        1 => Just(Event::StateRequest),
+        1 => explorer.clone().prop_map(Event::SupportedResources),
+        1 => explorer.clone().prop_map(Event::SupportedCombinations),
+        3 => (explorer.clone(), resource()).prop_map(|(id, r)| Event::Generate(id, r)),
+        2 => explorer.prop_map(Event::AvailableCells),
+    ]
+}
+
+/// Random events, mixed with runs where an explorer reserves cells, an
+/// asteroid makes the planet fire and rebuild, and the explorer then spends
+/// its reservation of `planet_resource`.
+fn events(planet_resource: BasicResourceType) -> impl Strategy<Value = Vec<Event>> {
+    let run = prop_oneof![
+        8 => event().prop_map(|event| vec![event]),
+        1 => (0u32..3, 0usize..4).prop_map(move |(explorer_id, sunrays)| {
+            let mut run = vec![Event::Sunray; sunrays];
+            run.extend([
+                Event::AvailableCells(explorer_id),
+                Event::Asteroid,
+                Event::Generate(explorer_id, planet_resource),
+            ]);
+            run
+        }),
+    ];
+    proptest::collection::vec(run, 0..60).prop_map(|runs| runs.concat())
+}
+
+proptest! {
+    #[test]
+    fn model_invariants_hold(
+        strategy in strategy(),
+        (planet_resource, events) in resource().prop_flat_map(|r| (Just(r), events(r))),
+        reservation in reservation(),
+        cell_selection in cell_selection(),
+    ) {
+        let mut config = PlanetConfig::new(1, strategy.clone(), Some(planet_resource));
+        config.reservation = reservation;
+        config.cell_selection = cell_selection;
+        let mut planet = SimulatedPlanet::new(config).expect("Failed to create planet");
+        let cells = planet.state.cells_iter().count() as u32;
+        // Every sunray ends up in a charged cell, spent on a rocket or a
+        // resource, or lost to overflow.
+        let (mut sunrays, mut overflow, mut generated) = (0, 0, 0);
+
+        for event in events {
+            let charged_before = planet.state.charged_count();
+            let rockets_before = planet.state.rockets_built();
+            match event {
+                Event::Sunray => {
+                    planet.sunray();
+                    sunrays += 1;
+                    if charged_before == cells && planet.state.rockets_built() == rockets_before {
+                        overflow += 1;
+                    }
+                    // The sunray charged a cell, or refilled the one a rocket was
+                    // just built from; otherwise it is overflow, which is only
+                    // allowed when every cell was already charged.
+                    let charged = planet.state.charged_count() == charged_before + 1
+                        || (planet.state.rockets_built() > rockets_before && planet.state.charged_count() == charged_before);
+                    prop_assert!(charged || charged_before == cells, "sunray lost while a cell was empty");
+                }
+                Event::Asteroid => {
+                    let rocket = planet.asteroid();
+                    if strategy == RocketStrategy::Disabled {
+                        prop_assert!(rocket.is_none(), "Disabled launched a rocket");
+                        prop_assert_eq!(planet.state.rockets_built(), 0);
+                    }
+                }
+                Event::StateRequest => {
+                    let dummy = planet.internal_state();
+                    prop_assert!(dummy.charged_cells_count as u32 <= planet.state.charged_count());
+                }
+                Event::SupportedResources(explorer_id) => {
+                    let resp = planet.explorer_msg(ExplorerToPlanet::SupportedResourceRequest { explorer_id });
+                    prop_assert!(resp.is_some());
+                }
+                Event::SupportedCombinations(explorer_id) => {
+                    let resp = planet.explorer_msg(ExplorerToPlanet::SupportedCombinationRequest { explorer_id });
+                    prop_assert!(resp.is_some());
+                }
+                Event::Generate(explorer_id, resource) => {
+                    let resp = planet.explorer_msg(ExplorerToPlanet::GenerateResourceRequest { explorer_id, resource });
+                    if let Some(PlanetToExplorer::GenerateResourceResponse { resource: Some(_) }) = resp {
+                        generated += 1;
+                        prop_assert_eq!(planet.state.charged_count() + 1, charged_before);
+                        if strategy == RocketStrategy::EmergencyReserve {
+                            prop_assert!(planet.state.charged_count() >= 1, "EmergencyReserve spent its last cell");
+                        }
+                        if strategy == RocketStrategy::Stockpile {
+                            prop_assert!(planet.state.charged_count() >= 2, "Stockpile sold a kept cell");
+                        }
+                    } else {
+                        prop_assert_eq!(planet.state.charged_count(), charged_before);
+                    }
+                }
+                Event::AvailableCells(explorer_id) => {
+                    let resp = planet.explorer_msg(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id });
+                    let Some(PlanetToExplorer::AvailableEnergyCellResponse { available_cells }) = resp else {
+                        return Err(TestCaseError::fail("no availability response"));
+                    };
+                    prop_assert!(available_cells <= planet.state.charged_count(), "disclosed more cells than charged");
+                }
+            }
+            prop_assert_eq!(
+                planet.state.charged_count() + planet.state.rockets_built() + generated + overflow,
+                sunrays,
+                "energy appeared or vanished"
+            );
+        }
+    }
+}
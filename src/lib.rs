@@ -1,3 +1,6 @@
+// The package name is fixed by the course setup, so the crate name is not snake case.
+#![allow(non_snake_case)]
+
 use common_game::components::planet::*;
 use common_game::components::resource::{BasicResource, BasicResourceType, Combinator, Generator};
 use common_game::components::rocket::Rocket;
@@ -9,8 +12,65 @@ use common_game::protocols::planet_explorer::*;
 use common_game::protocols::orchestrator_planet::*;
 use crossbeam_channel::{Receiver, Sender};
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use common_game::components::sunray::Sunray;
 
+mod admission;
+mod cell_selection;
+mod cells;
+mod config;
+#[cfg(feature = "serde")]
+mod config_file;
+mod evaluation;
+mod explorer_client;
+mod fleet;
+mod forecast;
+mod generation;
+#[cfg(any(test, feature = "fuzzing"))]
+pub mod fuzzing;
+mod handle;
+#[cfg(test)]
+mod invariants;
+mod planner;
+mod report;
+mod reservation;
+mod sessions;
+mod simulated;
+mod sink;
+mod tiers;
+mod verbosity;
+
+pub use admission::{AdmissionPolicy, ExplorerPolicy, Landing};
+pub use cell_selection::CellSelection;
+pub use cells::PlanetCells;
+pub use config::PlanetConfig;
+#[cfg(feature = "serde")]
+pub use config_file::ConfigError;
+pub use evaluation::{
+    evaluate_strategies, ComparisonTable, EvaluationParams, EventDistribution, StrategyScore,
+};
+pub use explorer_client::{CombineResult, ExplorerClient};
+pub use fleet::{Fleet, FleetResults};
+pub use forecast::{EnergyBudget, SunrayForecast};
+pub use generation::{BatchStop, GeneratedBatch};
+pub use handle::{PlanetError, PlanetHandle};
+pub use report::{FinalReport, SessionCounters};
+pub use planner::PlannerWeights;
+pub use reservation::ReservationPolicy;
+pub use sessions::ExplorerSession;
+pub use simulated::{SimulatedPlanet, SimulatedPlanetState, SimulatedRocket};
+pub use sink::{CallbackSink, CaptureSink, GlobalLogger, JsonLinesFile, LogSink, RingBufferSink, StdoutSink};
+pub use tiers::{ServiceTier, ServiceTiers};
+pub use verbosity::LogVerbosity;
+use admission::Rejections;
+use cell_selection::{CellSelector, Purpose};
+use forecast::{Arrival, Arrivals, PeriodBudget};
+use planner::{PlannerOption, Situation};
+use report::{PlanetStats, Reporter};
+use reservation::Reservations;
+use sessions::ExplorerSessions;
+
 const ORCHESTRATOR_ID: u32 = 0u32;
 
 
@@ -20,7 +80,11 @@ const ORCHESTRATOR_ID: u32 = 0u32;
 /// - `Default`: build a rocket only when an asteroid is coming.
 /// - `Safe`: always rebuild a rocket when there isn't any.
 /// - `EmergencyReserve`: same as `Safe`, but keeps one extra full cell reserved.
+/// - `Stockpile`: same as `Safe`, but keeps enough full cells to rebuild up to
+///   `PlanetConfig::stockpile_cap` rockets in a row.
+/// - `Planner`: weighs survival against explorer revenue at every decision.
 #[derive(Debug, PartialEq, Eq ,Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum RocketStrategy {
     /// Do not generate rockets under any condition.
     Disabled,
@@ -34,11 +98,71 @@ pub enum RocketStrategy {
 
     /// Same as `Safe`, but preserves one fully charged cell for emergencies.
     EmergencyReserve,
+
+    /// Keeps a logical stockpile of rockets: the one built plus a charged cell
+    /// for each of the next ones, up to `PlanetConfig::stockpile_cap`.
+    ///
+    /// A planet holds a single `Rocket`, so the stockpile is launched one per
+    /// asteroid and the next rocket is rebuilt right away from a kept cell.
+    /// Planet types that can't have rockets keep no cell.
+    Stockpile,
+
+    /// Decides whether to build a rocket by scoring building now, waiting to
+    /// build on demand and keeping the energy for explorers, with the weights
+    /// in `PlanetConfig::planner`. Every decision is logged with its scores.
+    Planner,
 }
 
-struct PlanetCoreThinkingModel {
+/// The planet AI: the [`PlanetAI`] implementation installed by [`create_planet`].
+///
+/// Its handlers are generic over [`PlanetCells`], so besides the runtime
+/// `PlanetState` they can be called directly on a [`SimulatedPlanetState`]
+/// (see [`SimulatedPlanet`]) without a planet thread.
+pub struct PlanetCoreThinkingModel {
     basic_resource: BasicResourceType,
     rocket_strategy: RocketStrategy,
+    reservation_policy: Option<ReservationPolicy>,
+    reservations: Reservations,
+    service_tiers: Option<ServiceTiers>,
+    stockpile_cap: u32,
+    planner: PlannerWeights,
+    arrivals: Arrivals,
+    energy_budget: Option<EnergyBudget>,
+    /// Budget of the current period, see [`EnergyBudget`].
+    budget: Option<PeriodBudget>,
+    cells: CellSelector,
+    explorer_sessions: ExplorerSessions,
+    explorer_policy: Option<Arc<dyn ExplorerPolicy>>,
+    /// Explorers on the planet that the policy refused to serve.
+    rejected: Rejections,
+    /// Logical clock, advanced once per handled event.
+    tick: u64,
+    sink: Arc<dyn LogSink>,
+    verbosity: LogVerbosity,
+    /// Shared with the [`Reporter`] that outlives the AI in the planet thread.
+    stats: Arc<Mutex<PlanetStats>>,
+    /// Counters of the current session, see [`SessionCounters`].
+    session: PlanetStats,
+    session_counters: SessionCounters,
+    sessions: u32,
+    running: bool,
+    housekeeping_pending: bool,
+}
+
+impl RocketStrategy {
+    /// Every strategy, in declaration order.
+    pub const ALL: [RocketStrategy; 6] = [
+        RocketStrategy::Disabled,
+        RocketStrategy::Default,
+        RocketStrategy::Safe,
+        RocketStrategy::EmergencyReserve,
+        RocketStrategy::Stockpile,
+        RocketStrategy::Planner,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = RocketStrategy> {
+        RocketStrategy::ALL.into_iter()
+    }
 }
 
 impl Display for RocketStrategy {
@@ -47,10 +171,165 @@ impl Display for RocketStrategy {
     }
 }
 
+/// Parses the name printed by `Display`, ignoring case, so `"safe"` and
+/// `"EmergencyReserve"` both work.
+impl FromStr for RocketStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RocketStrategy::iter()
+            .find(|strategy| strategy.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let names: Vec<_> = RocketStrategy::iter().map(|s| s.to_string()).collect();
+                format!("unknown rocket strategy {s:?}, expected one of {}", names.join(", "))
+            })
+    }
+}
+
 
 impl PlanetCoreThinkingModel {
-    fn charged_count( &mut self,
-            state: &mut PlanetState,) -> u32 {
+    pub fn new(config: &PlanetConfig) -> Self {
+        PlanetCoreThinkingModel {
+            rocket_strategy: config.rocket_strategy.clone(),
+            basic_resource: config.basic_resource.unwrap_or(BasicResourceType::Hydrogen),
+            reservation_policy: config.reservation.clone(),
+            reservations: Reservations::default(),
+            service_tiers: config.service_tiers.clone(),
+            stockpile_cap: config.stockpile_cap,
+            planner: config.planner.clone(),
+            arrivals: Arrivals::default(),
+            energy_budget: config.energy_budget.clone(),
+            budget: None,
+            cells: CellSelector::new(config.cell_selection),
+            explorer_sessions: ExplorerSessions::default(),
+            explorer_policy: config.explorer_policy.clone(),
+            rejected: Rejections::default(),
+            tick: 0,
+            sink: config
+                .log_sink
+                .clone()
+                .unwrap_or_else(|| Arc::new(GlobalLogger)),
+            verbosity: config.verbosity.clone(),
+            stats: Arc::default(),
+            session: PlanetStats::default(),
+            session_counters: config.session_counters.clone(),
+            sessions: 0,
+            running: false,
+            housekeeping_pending: false,
+        }
+    }
+
+    /// Builds the [`FinalReport`] of a planet in `state` and logs it.
+    pub fn final_report<S: PlanetCells>(&self, state: &S) -> FinalReport {
+        self.reporter().finish(state)
+    }
+
+    /// Logs the `Creation` event of a planet built from `config`.
+    fn log_creation(&self, config: &PlanetConfig) {
+        let PlanetConfig {
+            planet_id,
+            planet_type,
+            ref rocket_strategy,
+            basic_resource,
+            ..
+        } = *config;
+
+        self.log_lazy(
+            planet_id,
+            Participant::new(ActorType::SelfActor, planet_id),
+            EventType::InternalPlanetAction,
+            Channel::Info,
+            || {
+                let mut p = Payload::new();
+                p.insert("type".to_string(), "Creation".to_string());
+                p.insert("planetId".to_string(), planet_id.to_string());
+                p.insert("basicResourceRule".to_string(), format!("{:?}", basic_resource.unwrap_or(BasicResourceType::Hydrogen)));
+                p.insert("planetType".to_string(), format!("{:?}", planet_type));
+                p.insert("rocketStrategy".to_string(), format!("{:?}",rocket_strategy));
+                p
+            },
+        );
+    }
+
+    fn reporter(&self) -> Reporter {
+        Reporter {
+            stats: Arc::clone(&self.stats),
+            sink: Arc::clone(&self.sink),
+            verbosity: self.verbosity.clone(),
+            rejections: self.rejected.clone(),
+        }
+    }
+
+    /// Applies `update` to the lifetime and to the session counters.
+    fn record(&mut self, update: impl Fn(&mut PlanetStats)) {
+        update(&mut self.stats.lock().unwrap_or_else(|e| e.into_inner()));
+        update(&mut self.session);
+    }
+
+    /// Whether events of this type and channel pass the verbosity filter.
+    fn log_enabled(&self, event_type: &EventType, channel: &Channel) -> bool {
+        self.verbosity.enabled(event_type, channel)
+    }
+
+    /// Emits an event sent by the planet, building its payload only if the
+    /// event passes the verbosity filter: a filtered event costs one check.
+    fn log_lazy(
+        &self,
+        planet_id: u32,
+        receiver: Participant,
+        event_type: EventType,
+        channel: Channel,
+        payload: impl FnOnce() -> Payload,
+    ) {
+        // The eager baseline of the `log_overhead` benchmark builds it anyway
+        let payload = if self.verbosity.eager_payloads() { Ok(payload()) } else { Err(payload) };
+        if !self.log_enabled(&event_type, &channel) {
+            return;
+        }
+        self.sink.emit(&LogEvent::new(
+            Some(Participant::new(ActorType::Planet, planet_id)),
+            Some(receiver),
+            event_type,
+            channel,
+            payload.unwrap_or_else(|build| build()),
+        ));
+    }
+
+    /// Payload shared by every `GenerateResourceResponse` event.
+    fn generate_payload(&self, resource: BasicResourceType, tier: Option<ServiceTier>, cell: Option<usize>, result: &str) -> Payload {
+        let mut p = Payload::new();
+        p.insert("type".to_string(), "GenerateResourceResponse".to_string());
+        p.insert("ResourceRequested".to_string(), format!("{:?}", resource));
+        p.insert(
+            "rocketStrategy".to_string(),
+            self.rocket_strategy.to_string(),
+        );
+        if let Some(tier) = tier {
+            p.insert("serviceTier".to_string(), format!("{:?}", tier));
+        }
+        if let Some(cell) = cell {
+            p.insert("cellIndex".to_string(), cell.to_string());
+        }
+        p.insert("Result".to_string(), result.to_string());
+        p
+    }
+
+    /// The current service tier, `None` if tiers are disabled.
+    fn service_tier<S: PlanetCells>(&self, state: &S) -> Option<ServiceTier> {
+        let tiers = self.service_tiers.as_ref()?;
+        Some(tiers.tier(self.charged_count(state), state.cells_iter().count() as u32))
+    }
+
+    /// Whether the explorer may get new cells at this tier.
+    fn serves(&self, tier: Option<ServiceTier>, explorer_id: u32) -> bool {
+        match (&self.service_tiers, tier) {
+            (Some(tiers), Some(tier)) => tiers.serves(tier, explorer_id),
+            _ => true,
+        }
+    }
+
+    fn charged_count<S: PlanetCells>( &self,
+            state: &S,) -> u32 {
         let mut count = 0;
        state.cells_iter().for_each(|x| {
            if x.is_charged() {
@@ -59,570 +338,717 @@ impl PlanetCoreThinkingModel {
        });
         count
     }
-}
-impl PlanetAI for PlanetCoreThinkingModel {
-    // fn handle_orchestrator_msg(
-    //     &mut self,
-    //     state: &mut PlanetState,
-    //     _generator: &Generator,
-    //     _combinator: &Combinator,
-    //     msg: OrchestratorToPlanet,
-    // ) -> Option<PlanetToOrchestrator> {
-    //     match msg {
-    //         OrchestratorToPlanet::Sunray(sunray) => {
-    //             // let mut p = Payload::new();
-    //             // p.insert("type".to_string(), "SunrayAck".to_string());
-    //             // p.insert(
-    //             //     "rocketStrategy".to_string(),
-    //             //     self.rocket_strategy.to_string(),
-    //             // );
-    //             // p.insert(
-    //             //     "energyCellCountBeforeAck".to_string(),
-    //             //     format!("{}", self.charged_count(state)),
-    //             // );
-    //             // p.insert(
-    //             //     "rocketBeforeAck".to_string(),
-    //             //     format!("{}", state.has_rocket()),
-    //             // );
-    //             // let mut log = LogEvent::new(
-    //             //     ActorType::Planet,
-    //             //     state.id(),
-    //             //     ActorType::Orchestrator,
-    //             //     0u32.to_string(),
-    //             //     EventType::MessagePlanetToOrchestrator,
-    //             //     Channel::Debug,
-    //             //     Payload::new(), //fake payload
-    //             // );
-    //             //
-    //             // // Try to charge an empty cell
-    //             // let leftover = state.charge_cell(sunray);
-    //             //
-    //             // // Helper: check if this strategy allows building
-    //             // let can_build = |strategy: &RocketStrategy| -> bool {
-    //             //     match strategy {
-    //             //         RocketStrategy::Disabled => false,
-    //             //         RocketStrategy::Default => false, // never build on Sunray
-    //             //         RocketStrategy::Safe => true,
-    //             //         RocketStrategy::EmergencyReserve => true,
-    //             //     }
-    //             // };
-    //             //
-    //             // // CASE A — leftover == None  → at least one cell was uncharged
-    //             // if leftover.is_none() {
-    //             //     // Should we try building a rocket now?
-    //             //     if state.can_have_rocket()
-    //             //         && !state.has_rocket()
-    //             //         && can_build(&self.rocket_strategy)
-    //             //     {
-    //             //         let _ = try_build_rocket(state);
-    //             //     }
-    //             // } else {
-    //             //     // CASE B — leftover == Some(sunray) → all cells were full
-    //             //     if state.can_have_rocket()
-    //             //         && !state.has_rocket()
-    //             //         && can_build(&self.rocket_strategy)
-    //             //     {
-    //             //         if let Some(cell_index) = try_build_rocket(state) {
-    //             //             // Recharge the cell used to build the rocket with the leftover sunray
-    //             //             state.cell_mut(cell_index).charge(leftover.unwrap());
-    //             //         }
-    //             //     }
-    //             // }
-    //             //
-    //             // p.insert(
-    //             //     "energyCellCountAfterAck".to_string(),
-    //             //     format!("{}", self.charged_count(state)),
-    //             // );
-    //             // p.insert(
-    //             //     "rocketAfterAck".to_string(),
-    //             //     format!("{}", state.has_rocket()),
-    //             // );
-    //             //
-    //             // log.payload = p;
-    //             // log.emit();
-    //             //
-    //             // Some(PlanetToOrchestrator::SunrayAck {
-    //             //     planet_id: state.id(),
-    //             // })
-    //         }
-    //         // OrchestratorToPlanet::InternalStateRequest { .. } => match self.rocket_strategy {
-    //         //     RocketStrategy::EmergencyReserve => {
-    //         //         let mut dummy_state = PlanetState::to_dummy(state);
-    //         //
-    //         //         let mut p = Payload::new();
-    //         //         p.insert("type".to_string(), "InternalStateResponse".to_string());
-    //         //         p.insert(
-    //         //             "internalDummyState".to_string(),
-    //         //             format!("{:?}", dummy_state.clone()),
-    //         //         );
-    //         //         let mut log = LogEvent::new(
-    //         //             ActorType::Planet,
-    //         //             state.id(),
-    //         //             ActorType::Orchestrator,
-    //         //             0u32.to_string(),
-    //         //             EventType::MessagePlanetToOrchestrator,
-    //         //             Channel::Trace,
-    //         //             Payload::new(), //fake payload
-    //         //         );
-    //         //
-    //         //         dummy_state.charged_cells_count =
-    //         //             dummy_state.charged_cells_count.saturating_sub(1);
-    //         //
-    //         //         p.insert("sentDummyState".to_string(), format!("{:?}", dummy_state));
-    //         //         log.payload = p;
-    //         //         log.emit();
-    //         //
-    //         //         Some(PlanetToOrchestrator::InternalStateResponse {
-    //         //             planet_id: state.id(),
-    //         //             planet_state: dummy_state,
-    //         //         })
-    //         //     }
-    //         //     _ => {
-    //         //         let mut p = Payload::new();
-    //         //         p.insert("type".to_string(), "InternalStateResponse".to_string());
-    //         //         p.insert(
-    //         //             "DummyState".to_string(),
-    //         //             format!("{:?}", PlanetState::to_dummy(state)),
-    //         //         );
-    //         //         let log = LogEvent::new(
-    //         //             ActorType::Planet,
-    //         //             state.id(),
-    //         //             ActorType::Orchestrator,
-    //         //             0u32.to_string(),
-    //         //             EventType::MessagePlanetToOrchestrator,
-    //         //             Channel::Trace,
-    //         //             p,
-    //         //         );
-    //         //         log.emit();
-    //         //
-    //         //         Some(PlanetToOrchestrator::InternalStateResponse {
-    //         //             planet_id: state.id(),
-    //         //             planet_state: PlanetState::to_dummy(state),
-    //         //         })
-    //         //     }
-    //         // },
-    //         //OrchestratorToPlanet::Asteroid(_) => {}//handle_asteroid
-    //         // OrchestratorToPlanet::StartPlanetAI(_) => {}//start
-    //         // OrchestratorToPlanet::StopPlanetAI(_) => {}//stop
-    //         _ => None,
-    //     }
-    // }
 
-    fn handle_sunray(&mut self, state: &mut PlanetState, _generator: &Generator, _combinator: &Combinator, sunray: Sunray) {
-        let mut p = Payload::new();
-        p.insert("type".to_string(), "SunrayAck".to_string());
-        p.insert(
-            "rocketStrategy".to_string(),
-            self.rocket_strategy.to_string(),
-        );
-        p.insert(
-            "energyCellCountBeforeAck".to_string(),
-            format!("{}", self.charged_count(state)),
-        );
-        p.insert(
-            "rocketBeforeAck".to_string(),
-            format!("{}", state.has_rocket()),
-        );
-        let mut log = LogEvent::new(
-            Some(Participant::new(ActorType::Planet, state.id())),
-            Some(Participant::new(ActorType::Orchestrator, ORCHESTRATOR_ID)),
-            EventType::MessagePlanetToOrchestrator,
+    /// Advances the logical clock, releases expired reservations and records
+    /// the event for the forecast.
+    fn advance_clock(&mut self, arrival: Arrival) {
+        self.tick += 1;
+        self.reservations.expire(self.tick);
+        self.arrivals.observe(arrival);
+    }
+
+    /// Expected sunrays, asteroids and charged cells over the next `horizon`
+    /// events, from the arrival rates of the last 100 events.
+    pub fn forecast<S: PlanetCells>(&self, state: &S, horizon: u32) -> SunrayForecast {
+        SunrayForecast::new(&self.arrivals, horizon, self.charged_count(state), state.cells_iter().count() as u32)
+    }
+
+    /// Cells left in the energy budget of the current period, `None` if the
+    /// budget is disabled. Starts a new period when the current one is over.
+    fn budget_left<S: PlanetCells>(&mut self, state: &S) -> Option<u32> {
+        let policy = self.energy_budget.as_ref()?;
+        if self.budget.is_none_or(|budget| self.tick >= budget.ends_at) {
+            let forecast = self.forecast(state, policy.period);
+            let builds_rockets = state.can_have_rocket() && self.rocket_strategy != RocketStrategy::Disabled;
+            let rocket_cells = u32::from(builds_rockets && !state.has_rocket());
+            let cells = policy.cells(&forecast, self.charged_count(state), builds_rockets, rocket_cells);
+            self.budget = Some(PeriodBudget { ends_at: self.tick + u64::from(policy.period), cells, sold: 0 });
+            self.log_lazy(
+                state.id(),
+                Participant::new(ActorType::SelfActor, state.id()),
+                EventType::InternalPlanetAction,
+                Channel::Debug,
+                || {
+                    let mut p = Payload::new();
+                    p.insert("type".to_string(), "EnergyBudget".to_string());
+                    p.insert("period".to_string(), forecast.horizon.to_string());
+                    p.insert("forecastSunrays".to_string(), format!("{:.2}", forecast.sunrays));
+                    p.insert("forecastAsteroids".to_string(), format!("{:.2}", forecast.asteroids));
+                    p.insert("forecastChargedCells".to_string(), format!("{:.2}", forecast.charged_cells));
+                    p.insert("budgetCells".to_string(), cells.to_string());
+                    p
+                },
+            );
+        }
+        self.budget.map(|budget| budget.left())
+    }
+
+    /// Charged cells kept for the next rockets of a `Stockpile` planet.
+    fn stockpile_cells<S: PlanetCells>(&self, state: &S) -> u32 {
+        if self.rocket_strategy != RocketStrategy::Stockpile || !state.can_have_rocket() {
+            return 0;
+        }
+        self.charged_count(state).min(self.stockpile_cap.saturating_sub(1))
+    }
+
+    /// Rockets a `Stockpile` planet can launch in a row, `None` for the other
+    /// strategies.
+    fn stockpile<S: PlanetCells>(&self, state: &S) -> Option<u32> {
+        (self.rocket_strategy == RocketStrategy::Stockpile)
+            .then(|| u32::from(state.has_rocket()) + self.stockpile_cells(state))
+    }
+
+    /// Asks the `Planner` strategy whether to build a rocket now, logging the
+    /// scored decision. Only called when the planet has no rocket and the
+    /// strategy is `Planner`.
+    fn planner_builds<S: PlanetCells>(&self, state: &S, handler: &str, asteroid_now: bool, overflow: bool) -> bool {
+        if !state.can_have_rocket() || self.charged_count(state) <= self.reservations.total() {
+            return false;
+        }
+        // The same recent rates as the sunray forecast
+        let risk = if asteroid_now { 1.0 } else { self.arrivals.rate(Arrival::Asteroid) };
+        let demand = self.arrivals.rate(Arrival::Explorer);
+        let decision = self.planner.decide(Situation { risk, demand, overflow });
+        self.log_lazy(
+            state.id(),
+            Participant::new(ActorType::SelfActor, state.id()),
+            EventType::InternalPlanetAction,
             Channel::Debug,
-            Payload::new(), //fake payload
+            || decision.payload(handler),
         );
+        decision.choice == PlannerOption::BuildRocket
+    }
+
+    /// Charged cells held back by the strategy, never sold to explorers.
+    fn kept_count<S: PlanetCells>(&self, state: &S) -> u32 {
+        let kept = match self.rocket_strategy {
+            RocketStrategy::EmergencyReserve => 1,
+            RocketStrategy::Stockpile => self.stockpile_cells(state),
+            _ => 0,
+        };
+        // Cells dedicated to rockets also cover what the strategy keeps
+        kept.max(self.cells.closed_to_trade(state))
+    }
+
+    /// Charged cells that are neither held back by the strategy nor reserved
+    /// by an explorer.
+    fn unreserved_count<S: PlanetCells>(&self, state: &S) -> u32 {
+        self.charged_count(state)
+            .saturating_sub(self.kept_count(state) + self.reservations.total())
+    }
+
+    /// Charged cells `explorer_id` can be served from: the unreserved ones
+    /// and its own reservation. A reservation does not reach into the kept
+    /// cells, which rockets may have used up since it was granted.
+    fn available_to<S: PlanetCells>(&self, state: &S, explorer_id: u32) -> u32 {
+        let others = self.reservations.total() - self.reservations.held_by(explorer_id);
+        self.charged_count(state)
+            .saturating_sub(self.kept_count(state) + others)
+    }
+
+    pub fn handle_sunray<S: PlanetCells>(&mut self, state: &mut S, _generator: &Generator, _combinator: &Combinator, sunray: Sunray) {
+        self.advance_clock(Arrival::Sunray);
+        self.housekeeping(state);
+        // Taken only if the acknowledgement is going to be logged
+        let before = self
+            .log_enabled(&EventType::MessagePlanetToOrchestrator, &Channel::Debug)
+            .then(|| (self.charged_count(state), state.has_rocket()));
 
         // Try to charge an empty cell
         let leftover = state.charge_cell(sunray);
+        let rockets_before = state.has_rocket();
 
         // Helper: check if this strategy allows building
-        let can_build = |strategy: &RocketStrategy| -> bool {
-            match strategy {
+        let can_build = |state: &S, overflow: bool| -> bool {
+            match self.rocket_strategy {
                 RocketStrategy::Disabled => false,
                 RocketStrategy::Default => false, // never build on Sunray
                 RocketStrategy::Safe => true,
                 RocketStrategy::EmergencyReserve => true,
+                RocketStrategy::Stockpile => true,
+                RocketStrategy::Planner => self.planner_builds(state, "Sunray", false, overflow),
             }
         };
 
-        // CASE A — leftover == None  → at least one cell was uncharged
-        if leftover.is_none() {
-            // Should we try building a rocket now?
-            if state.can_have_rocket()
-                && !state.has_rocket()
-                && can_build(&self.rocket_strategy)
-            {
-                let _ = try_build_rocket(state);
+        let reserved = self.reservations.total();
+        let rocket_cell = match leftover {
+            // CASE A — leftover == None  → at least one cell was uncharged
+            None => {
+                // Should we try building a rocket now?
+                if state.can_have_rocket()
+                    && !state.has_rocket()
+                    && can_build(state, false)
+                {
+                    try_build_rocket(state, reserved, &mut self.cells)
+                } else {
+                    None
+                }
             }
-        } else {
             // CASE B — leftover == Some(sunray) → all cells were full
-            if state.can_have_rocket()
-                && !state.has_rocket()
-                && can_build(&self.rocket_strategy)
-            {
-                if let Some(cell_index) = try_build_rocket(state) {
+            Some(sunray) => {
+                if state.can_have_rocket()
+                    && !state.has_rocket()
+                    && can_build(state, true)
+                    && let Some(cell_index) = try_build_rocket(state, reserved, &mut self.cells)
+                {
                     // Recharge the cell used to build the rocket with the leftover sunray
-                    state.cell_mut(cell_index).charge(leftover.unwrap());
+                    state.cell_mut(cell_index).charge(sunray);
+                    Some(cell_index)
+                } else {
+                    None
                 }
             }
-        }
+        };
 
-        p.insert(
-            "energyCellCountAfterAck".to_string(),
-            format!("{}", self.charged_count(state)),
-        );
-        p.insert(
-            "rocketAfterAck".to_string(),
-            format!("{}", state.has_rocket()),
-        );
+        let built_rocket = !rockets_before && state.has_rocket();
+        self.record(|stats| {
+            stats.sunrays += 1;
+            stats.rockets_built += u64::from(built_rocket);
+        });
 
-        log.payload = p;
-        log.emit();
+        if let Some((cells_before, rocket_before)) = before {
+            self.log_lazy(
+                state.id(),
+                Participant::new(ActorType::Orchestrator, ORCHESTRATOR_ID),
+                EventType::MessagePlanetToOrchestrator,
+                Channel::Debug,
+                || {
+                    let mut p = Payload::new();
+                    p.insert("type".to_string(), "SunrayAck".to_string());
+                    p.insert(
+                        "rocketStrategy".to_string(),
+                        self.rocket_strategy.to_string(),
+                    );
+                    p.insert("energyCellCountBeforeAck".to_string(), cells_before.to_string());
+                    p.insert("rocketBeforeAck".to_string(), rocket_before.to_string());
+                    p.insert(
+                        "energyCellCountAfterAck".to_string(),
+                        self.charged_count(state).to_string(),
+                    );
+                    p.insert(
+                        "rocketAfterAck".to_string(),
+                        state.has_rocket().to_string(),
+                    );
+                    if let Some(cell) = rocket_cell {
+                        p.insert("rocketCells".to_string(), format!("{:?}", [cell]));
+                    }
+                    if let Some(stockpile) = self.stockpile(state) {
+                        p.insert("rocketStockpile".to_string(), stockpile.to_string());
+                    }
+                    p
+                },
+            );
+        }
     }
 
-    fn handle_asteroid(
+    pub fn handle_asteroid<S: PlanetCells>(
         &mut self,
-        state: &mut PlanetState,
+        state: &mut S,
         _generator: &Generator,
         _combinator: &Combinator,
-    ) -> Option<Rocket> {
-        let mut p = Payload::new();
-        p.insert("type".to_string(), "AsteroidAck".to_string());
-        p.insert("HadRocket".to_string(), format!("{:?}", state.has_rocket()));
-        p.insert(
-            "rocketStrategy".to_string(),
-            self.rocket_strategy.to_string(),
-        );
-        let mut log = LogEvent::new(
-            Some(Participant::new(ActorType::Planet, state.id())),
-            Some(Participant::new(ActorType::Orchestrator, ORCHESTRATOR_ID)),
-            EventType::MessagePlanetToOrchestrator,
-            Channel::Info,
-            Payload::new(),
-        );
+    ) -> Option<S::Rocket> {
+        self.advance_clock(Arrival::Asteroid);
+        self.housekeeping(state);
+        let had_rocket = state.has_rocket();
+        // Cells spent on rockets: one built on demand, one rebuilt after the launch
+        let mut rocket_cells = Vec::new();
+
+        let rocket = if !state.can_have_rocket() {
+            None
+        } else {
+            // A stockpile that ran out of rockets may still have a cell left
+            if !state.has_rocket()
+                && match self.rocket_strategy {
+                    RocketStrategy::Default | RocketStrategy::Stockpile => true,
+                    RocketStrategy::Planner => self.planner_builds(state, "Asteroid", true, false),
+                    _ => false,
+                }
+            {
+                rocket_cells.extend(try_build_rocket(state, self.reservations.total(), &mut self.cells));
+            }
+            if !state.has_rocket() {
+                None
+            } else {
+                let rocket = state.take_rocket();
+                if match self.rocket_strategy {
+                    RocketStrategy::Safe | RocketStrategy::EmergencyReserve | RocketStrategy::Stockpile => true,
+                    RocketStrategy::Planner => self.planner_builds(state, "Asteroid", false, false),
+                    _ => false,
+                } {
+                    rocket_cells.extend(try_build_rocket(state, self.reservations.total(), &mut self.cells));
+                }
+                rocket
+            }
+        };
+        self.record(|stats| {
+            stats.asteroids += 1;
+            stats.asteroids_deflected += u64::from(rocket.is_some());
+            stats.rockets_built += rocket_cells.len() as u64;
+        });
 
-        if !state.can_have_rocket() {
-            log.payload = p;
-            log.emit();
-            return None;
-        }
-        if self.rocket_strategy == RocketStrategy::Default {
-            let result = try_build_rocket(state);
-            if result.is_some() {
+        self.log_lazy(
+            state.id(),
+            Participant::new(ActorType::Orchestrator, ORCHESTRATOR_ID),
+            EventType::MessagePlanetToOrchestrator,
+            Channel::Info,
+            || {
+                let mut p = Payload::new();
+                p.insert("type".to_string(), "AsteroidAck".to_string());
+                p.insert("HadRocket".to_string(), format!("{:?}", had_rocket));
                 p.insert(
-                    "Built a Rocket, energyCellCount".to_string(),
-                    format!("{:?}", self.charged_count(state)),
+                    "rocketStrategy".to_string(),
+                    self.rocket_strategy.to_string(),
                 );
-            }
-        }
-        if !state.has_rocket() {
-            log.payload = p;
-            log.emit();
-            return None;
+                if !rocket_cells.is_empty() {
+                    p.insert(
+                        "Built a Rocket, energyCellCount".to_string(),
+                        format!("{:?}", self.charged_count(state)),
+                    );
+                    p.insert("rocketCells".to_string(), format!("{:?}", rocket_cells));
+                }
+                if let Some(stockpile) = self.stockpile(state) {
+                    p.insert("rocketStockpile".to_string(), stockpile.to_string());
+                }
+                p
+            },
+        );
+        rocket
+    }
+
+    /// Called when the AI is started, the first time and after every stop.
+    ///
+    /// The runtime only lends the state immutably here, so the strategy
+    /// housekeeping (rebuilding a missing rocket for `Safe`,
+    /// `EmergencyReserve` and `Stockpile`) runs at the beginning of the next handled message.
+    pub fn on_start<S: PlanetCells>(&mut self, state: &S, _generator: &Generator, _combinator: &Combinator) {
+        self.running = true;
+        self.sessions += 1;
+        let reset = self.session_counters == SessionCounters::ResetOnStart;
+        if reset {
+            self.session = PlanetStats::default();
         }
+        self.housekeeping_pending = matches!(
+            self.rocket_strategy,
+            RocketStrategy::Safe | RocketStrategy::EmergencyReserve | RocketStrategy::Stockpile
+        );
 
-        let rocket = state.take_rocket();
-        if self.rocket_strategy == RocketStrategy::Safe
-            || self.rocket_strategy == RocketStrategy::EmergencyReserve
-        {
-            let result = try_build_rocket(state);
-            if result.is_some() {
+        self.log_lazy(
+            state.id(),
+            Participant::new(ActorType::SelfActor, state.id()),
+            EventType::InternalPlanetAction,
+            Channel::Info,
+            || {
+                let mut p = Payload::new();
+                p.insert("type".to_string(), "StartAI".to_string());
+                p.insert("session".to_string(), self.sessions.to_string());
+                p.insert("sessionCountersReset".to_string(), reset.to_string());
                 p.insert(
-                    "Built a Rocket, energyCellCount".to_string(),
-                    format!("{:?}", self.charged_count(state)),
+                    "energyCellCount".to_string(),
+                    self.charged_count(state).to_string(),
                 );
-            }
-        }
-        log.payload = p;
-        log.emit();
-        rocket
+                p
+            },
+        );
     }
 
-    // fn start(&mut self, state: &PlanetState) {
-    //     let mut p = Payload::new();
-    //     p.insert("type".to_string(), "StartAI".to_string());
-    //     LogEvent::new(
-    //         ActorType::Planet,
-    //         state.id(),
-    //         ActorType::SelfActor,
-    //         0u32.to_string(),
-    //         EventType::InternalPlanetAction,
-    //         Channel::Info,
-    //         p,
-    //     )
-    //     .emit();
-    //
-    //     self.running = true;
-    // }
-    //
-    // fn stop(&mut self, state: &PlanetState) {
-    //     let mut p = Payload::new();
-    //     p.insert("type".to_string(), "StopAI".to_string());
-    //     LogEvent::new(
-    //         ActorType::Planet,
-    //         state.id(),
-    //         ActorType::SelfActor,
-    //         0u32.to_string(),
-    //         EventType::InternalPlanetAction,
-    //         Channel::Info,
-    //         p,
-    //     )
-    //     .emit();
-    //
-    //     self.running = false;
-    // }
-
-    fn handle_internal_state_req(&mut self, state: &mut PlanetState, _generator: &Generator, _combinator: &Combinator) -> DummyPlanetState {
-        match self.rocket_strategy {
-            RocketStrategy::EmergencyReserve => {
-                let mut dummy_state = PlanetState::to_dummy(state);
+    /// Called when the AI is stopped; logs what happened during the session.
+    pub fn on_stop<S: PlanetCells>(&mut self, state: &S, _generator: &Generator, _combinator: &Combinator) {
+        self.running = false;
+        self.housekeeping_pending = false;
 
+        self.log_lazy(
+            state.id(),
+            Participant::new(ActorType::SelfActor, state.id()),
+            EventType::InternalPlanetAction,
+            Channel::Info,
+            || {
+                let session = &self.session;
                 let mut p = Payload::new();
-                p.insert("type".to_string(), "InternalStateResponse".to_string());
+                p.insert("type".to_string(), "StopAI".to_string());
+                p.insert("session".to_string(), self.sessions.to_string());
+                p.insert("sunrays".to_string(), session.sunrays.to_string());
+                p.insert("asteroids".to_string(), session.asteroids.to_string());
+                p.insert("explorerRequests".to_string(), session.explorer_requests.to_string());
+                p.insert("resourcesGenerated".to_string(), session.resources_generated.to_string());
                 p.insert(
-                    "internalDummyState".to_string(),
-                    format!("{:?}", dummy_state.clone()),
-                );
-                let mut log = LogEvent::new(
-                    Some(Participant::new(ActorType::Planet, state.id())),
-                    Some(Participant::new(ActorType::Orchestrator, ORCHESTRATOR_ID)),
-                    EventType::MessagePlanetToOrchestrator,
-                    Channel::Trace,
-                    Payload::new(), //fake payload
+                    "energyCellCount".to_string(),
+                    self.charged_count(state).to_string(),
                 );
+                p.insert("hasRocket".to_string(), state.has_rocket().to_string());
+                p
+            },
+        );
+    }
 
-                dummy_state.charged_cells_count =
-                    dummy_state.charged_cells_count.saturating_sub(1);
+    /// Whether the AI is between `on_start` and `on_stop`.
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
 
-                p.insert("sentDummyState".to_string(), format!("{:?}", dummy_state));
-                log.payload = p;
-                log.emit();
+    /// Runs the housekeeping scheduled by `on_start`, if any.
+    fn housekeeping<S: PlanetCells>(&mut self, state: &mut S) {
+        if !std::mem::take(&mut self.housekeeping_pending) {
+            return;
+        }
+        if state.can_have_rocket()
+            && !state.has_rocket()
+            && try_build_rocket(state, self.reservations.total(), &mut self.cells).is_some()
+        {
+            self.record(|stats| stats.rockets_built += 1);
+        }
+    }
 
-                dummy_state
-            }
-            _ => {
+    /// Asks the explorer policy whether to serve an explorer landing on the
+    /// planet, and opens its session if so.
+    pub fn on_explorer_arrival<S: PlanetCells>(&mut self, state: &mut S, _generator: &Generator, _combinator: &Combinator, explorer_id: u32) {
+        let landing = Landing {
+            explorer_id,
+            explorers_on_planet: self.explorer_sessions.len(),
+            charged_cells: self.charged_count(state),
+            total_cells: state.cells_iter().count() as u32,
+        };
+        let decision = self
+            .explorer_policy
+            .as_ref()
+            .map_or(Ok(()), |policy| policy.admit(&landing));
+        self.log_lazy(
+            state.id(),
+            Participant::new(ActorType::Orchestrator, ORCHESTRATOR_ID),
+            EventType::MessagePlanetToOrchestrator,
+            Channel::Info,
+            || {
+                let mut p = Payload::new();
+                p.insert("type".to_string(), "ExplorerAdmission".to_string());
+                p.insert("explorerId".to_string(), explorer_id.to_string());
+                match &decision {
+                    Ok(()) => {
+                        p.insert("decision".to_string(), "Accepted".to_string());
+                    }
+                    Err(reason) => {
+                        p.insert("decision".to_string(), "Rejected".to_string());
+                        p.insert("reason".to_string(), reason.clone());
+                    }
+                }
+                p
+            },
+        );
+        if let Err(reason) = decision {
+            self.rejected.insert(explorer_id, reason);
+            self.explorer_sessions.close(explorer_id);
+            return;
+        }
+        self.rejected.remove(explorer_id);
+        self.explorer_sessions.open(explorer_id, self.tick);
+        self.log_lazy(
+            state.id(),
+            Participant::new(ActorType::Explorer, explorer_id),
+            EventType::InternalPlanetAction,
+            Channel::Debug,
+            || {
+                let mut p = Payload::new();
+                p.insert("type".to_string(), "ExplorerSessionOpened".to_string());
+                p.insert("arrivedAt".to_string(), self.tick.to_string());
+                p
+            },
+        );
+    }
+
+    /// Closes the session of a leaving explorer, logs its summary and tells
+    /// the explorer policy.
+    pub fn on_explorer_departure<S: PlanetCells>(&mut self, state: &mut S, _generator: &Generator, _combinator: &Combinator, explorer_id: u32) {
+        self.rejected.remove(explorer_id);
+        let session = self.explorer_sessions.close(explorer_id);
+        if let Some(policy) = &self.explorer_policy {
+            policy.on_departure(explorer_id, session.as_ref());
+        }
+        let Some(session) = session else {
+            return;
+        };
+        self.log_lazy(
+            state.id(),
+            Participant::new(ActorType::Explorer, explorer_id),
+            EventType::InternalPlanetAction,
+            Channel::Info,
+            || session.summary(explorer_id, self.tick),
+        );
+    }
+
+    /// The session of an explorer currently on the planet.
+    pub fn explorer_session(&self, explorer_id: u32) -> Option<&ExplorerSession> {
+        self.explorer_sessions.get(explorer_id)
+    }
+
+    pub fn handle_internal_state_req<S: PlanetCells>(&mut self, state: &mut S, _generator: &Generator, _combinator: &Combinator) -> DummyPlanetState {
+        self.advance_clock(Arrival::Other);
+        self.housekeeping(state);
+        self.record(|stats| stats.internal_state_requests += 1);
+        let mut dummy_state = state.to_dummy();
+        // EmergencyReserve hides its last cell from the orchestrator
+        let deceive = self.rocket_strategy == RocketStrategy::EmergencyReserve;
+
+        self.log_lazy(
+            state.id(),
+            Participant::new(ActorType::Orchestrator, ORCHESTRATOR_ID),
+            EventType::MessagePlanetToOrchestrator,
+            Channel::Trace,
+            || {
                 let mut p = Payload::new();
                 p.insert("type".to_string(), "InternalStateResponse".to_string());
-                p.insert(
-                    "DummyState".to_string(),
-                    format!("{:?}", PlanetState::to_dummy(state)),
-                );
-                let log = LogEvent::new(
-                    Some(Participant::new(ActorType::Planet, state.id())),
-                    Some(Participant::new(ActorType::Orchestrator, ORCHESTRATOR_ID)),
-                    EventType::MessagePlanetToOrchestrator,
-                    Channel::Trace,
-                    p,
-                );
-                log.emit();
+                if deceive {
+                    let mut sent = dummy_state.clone();
+                    sent.charged_cells_count = sent.charged_cells_count.saturating_sub(1);
+                    p.insert(
+                        "internalDummyState".to_string(),
+                        format!("{:?}", dummy_state),
+                    );
+                    p.insert("sentDummyState".to_string(), format!("{:?}", sent));
+                } else {
+                    p.insert("DummyState".to_string(), format!("{:?}", dummy_state));
+                }
+                p
+            },
+        );
 
-                PlanetState::to_dummy(state)
-            }
+        if deceive {
+            dummy_state.charged_cells_count = dummy_state.charged_cells_count.saturating_sub(1);
         }
+        dummy_state
     }
 
-    fn handle_explorer_msg(
+    pub fn handle_explorer_msg<S: PlanetCells>(
         &mut self,
-        state: &mut PlanetState,
+        state: &mut S,
         generator: &Generator,
         combinator: &Combinator,
         msg: ExplorerToPlanet,
     ) -> Option<PlanetToExplorer> {
-        match msg {
-            ExplorerToPlanet::SupportedResourceRequest { explorer_id } => {
-                let mut p = Payload::new();
-                p.insert("type".to_string(), "SupportedResourceResponse".to_string());
-                p.insert(
-                    "Recipes".to_string(),
-                    format!("{:?}", generator.all_available_recipes()),
-                );
-                let log = LogEvent::new(
-                    Some(Participant::new(ActorType::Planet, state.id())),
-                    Some(Participant::new(ActorType::Explorer, explorer_id)),
-                    EventType::MessagePlanetToExplorer,
-                    Channel::Trace,
-                    p,
-                );
-                log.emit();
+        self.advance_clock(Arrival::Explorer);
+        self.housekeeping(state);
+        let explorer_id = msg.explorer_id();
+        if self.rejected.contains(explorer_id) {
+            self.record(|stats| stats.explorer_requests += 1);
+            self.log_lazy(
+                state.id(),
+                Participant::new(ActorType::Explorer, explorer_id),
+                EventType::MessagePlanetToExplorer,
+                Channel::Debug,
+                || {
+                    let mut p = Payload::new();
+                    p.insert("type".to_string(), "ExplorerRejected".to_string());
+                    p.insert("request".to_string(), format!("{:?}", ExplorerToPlanetKind::from(&msg)));
+                    p
+                },
+            );
+            return None;
+        }
+        let response = self.respond_to_explorer(state, generator, combinator, msg);
+        self.explorer_sessions.record(explorer_id, |session| session.requests += 1);
+        self.record(|stats| {
+            stats.explorer_requests += 1;
+            if response.is_some() {
+                stats.explorers_served.insert(explorer_id);
+            }
+        });
+        response
+    }
 
-                Some(PlanetToExplorer::SupportedResourceResponse {
-                    resource_list: generator.all_available_recipes(),
-                })
+    /// Generates up to `quantity` units of `resource` for `explorer_id` in a
+    /// single step: no sunray or asteroid can come in between, so every unit
+    /// is checked against the same cells, reservations and budget as a
+    /// `GenerateResourceRequest` would be, one after the other.
+    ///
+    /// The batch stops at the first unit the planet would refuse, and counts
+    /// as one explorer request. Each unit is logged as a
+    /// `GenerateResourceResponse`, the batch as a `GenerateBatchResponse`.
+    pub fn generate_batch<S: PlanetCells>(
+        &mut self,
+        state: &mut S,
+        generator: &Generator,
+        explorer_id: u32,
+        resource: BasicResourceType,
+        quantity: u32,
+    ) -> GeneratedBatch {
+        self.advance_clock(Arrival::Explorer);
+        self.housekeeping(state);
+        let mut resources = Vec::new();
+        let stop = if self.rejected.contains(explorer_id) {
+            BatchStop::Rejected
+        } else {
+            loop {
+                if resources.len() as u32 == quantity {
+                    break BatchStop::Completed;
+                }
+                match self.generate_one(state, generator, explorer_id, resource) {
+                    Ok(Some(generated)) => resources.push(generated),
+                    Ok(None) => break BatchStop::GenerationFailed,
+                    Err(stop) => break stop,
+                }
             }
-            ExplorerToPlanet::SupportedCombinationRequest { explorer_id } => {
-                let mut p = Payload::new();
-                p.insert(
-                    "type".to_string(),
-                    "SupportedCombinationResponse".to_string(),
-                );
+        };
+        self.explorer_sessions.record(explorer_id, |session| session.requests += 1);
+        self.record(|stats| {
+            stats.explorer_requests += 1;
+            if !resources.is_empty() {
+                stats.explorers_served.insert(explorer_id);
+            }
+        });
+        let explorer = Participant::new(ActorType::Explorer, explorer_id);
+        self.log_lazy(state.id(), explorer, EventType::MessagePlanetToExplorer, Channel::Debug, || {
+            let mut p = Payload::new();
+            p.insert("type".to_string(), "GenerateBatchResponse".to_string());
+            p.insert("resource".to_string(), format!("{:?}", resource));
+            p.insert("requested".to_string(), quantity.to_string());
+            p.insert("generated".to_string(), resources.len().to_string());
+            p.insert("stopReason".to_string(), format!("{:?}", stop));
+            p
+        });
+        GeneratedBatch { resources, stop }
+    }
+
+    /// Generates one unit of `resource` for `explorer_id`, logging the
+    /// outcome as a `GenerateResourceResponse`.
+    ///
+    /// `Ok(None)` is a cell spent by the planet without a resource coming
+    /// out of it; an `Err` is a request the planet does not answer.
+    fn generate_one<S: PlanetCells>(
+        &mut self,
+        state: &mut S,
+        generator: &Generator,
+        explorer_id: u32,
+        resource: BasicResourceType,
+    ) -> Result<Option<BasicResource>, BatchStop> {
+        let planet_id = state.id();
+        let explorer = Participant::new(ActorType::Explorer, explorer_id);
+        // An explorer holding a reservation may spend it; everyone else
+        // competes for the cells nobody has reserved, if the service
+        // tier lets them.
+        let tier = self.service_tier(state);
+        let holds_reservation = self.reservations.held_by(explorer_id) > 0;
+        if !holds_reservation && !self.serves(tier, explorer_id) {
+            self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Debug, || {
+                self.generate_payload(resource, tier, None, "Failure")
+            });
+            return Err(BatchStop::NotServed);
+        }
+        // Reserved cells are already promised out of the budget
+        let budget_left = self.budget_left(state);
+        if !holds_reservation
+            && budget_left.is_some_and(|left| left <= self.reservations.total())
+        {
+            self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Debug, || {
+                let mut p = self.generate_payload(resource, tier, None, "Failure");
+                p.insert("budgetExhausted".to_string(), "true".to_string());
+                p
+            });
+            return Err(BatchStop::BudgetExhausted);
+        }
+        if self.available_to(state, explorer_id) == 0 {
+            self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Debug, || {
+                let mut p = self.generate_payload(resource, tier, None, "Failure");
+                if self.rocket_strategy == RocketStrategy::EmergencyReserve {
+                    p.insert(
+                        "energyCellCount".to_string(),
+                        format!("{} , this is intended behavior", self.charged_count(state)),
+                    );
+                }
                 p.insert(
-                    "Recipes".to_string(),
-                    format!("{:?}", combinator.all_available_recipes()),
+                    "reservedCells".to_string(),
+                    self.reservations.total().to_string(),
                 );
-                let log = LogEvent::new(
-                    Some(Participant::new(ActorType::Planet, state.id())),
-                    Some(Participant::new(ActorType::Explorer, explorer_id)),
-                    EventType::MessagePlanetToExplorer,
-                    Channel::Trace,
-                    p,
-                );
-                log.emit();
+                p
+            });
+            return Err(BatchStop::NoFreeCell);
+        }
+        // The planet only generates its own resource, and picking a cell
+        // moves the round-robin cursor
+        if resource != self.basic_resource {
+            self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Warning, || {
+                self.generate_payload(resource, tier, None, "Failure")
+            });
+            return Err(BatchStop::Unsupported);
+        }
+        let Some(cell_index) = self.cells.pick(state, Purpose::Trade) else {
+            self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Debug, || {
+                self.generate_payload(resource, tier, None, "Failure")
+            });
+            return Err(BatchStop::NoFreeCell);
+        };
+        let cell = state.cell_mut(cell_index);
+        let generated = generation::make(generator, resource, cell);
+        let spent = !cell.is_charged();
+        self.explorer_sessions.record(explorer_id, |session| {
+            session.cells_consumed += u64::from(spent);
+            session.resources_received += u64::from(generated.is_some());
+        });
+        self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Debug, || {
+            self.generate_payload(resource, tier, Some(cell_index), "Success")
+        });
+        if generated.is_some() {
+            if holds_reservation {
+                self.reservations.consume(explorer_id);
+            }
+            if let Some(budget) = &mut self.budget {
+                budget.sold += 1;
+            }
+            self.record(|stats| stats.resources_generated += 1);
+        }
+        Ok(generated)
+    }
 
-                Some(PlanetToExplorer::SupportedCombinationResponse {
-                    combination_list: combinator.all_available_recipes(),
-                })
+    fn respond_to_explorer<S: PlanetCells>(
+        &mut self,
+        state: &mut S,
+        generator: &Generator,
+        combinator: &Combinator,
+        msg: ExplorerToPlanet,
+    ) -> Option<PlanetToExplorer> {
+        let planet_id = state.id();
+        let explorer = Participant::new(ActorType::Explorer, msg.explorer_id());
+        match msg {
+            ExplorerToPlanet::SupportedResourceRequest { .. } => {
+                let resource_list = generator.all_available_recipes();
+                self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Trace, || {
+                    let mut p = Payload::new();
+                    p.insert("type".to_string(), "SupportedResourceResponse".to_string());
+                    p.insert("Recipes".to_string(), format!("{:?}", resource_list));
+                    p
+                });
+
+                Some(PlanetToExplorer::SupportedResourceResponse { resource_list })
+            }
+            ExplorerToPlanet::SupportedCombinationRequest { .. } => {
+                let combination_list = combinator.all_available_recipes();
+                self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Trace, || {
+                    let mut p = Payload::new();
+                    p.insert(
+                        "type".to_string(),
+                        "SupportedCombinationResponse".to_string(),
+                    );
+                    p.insert("Recipes".to_string(), format!("{:?}", combination_list));
+                    p
+                });
+
+                Some(PlanetToExplorer::SupportedCombinationResponse { combination_list })
             }
             ExplorerToPlanet::GenerateResourceRequest {
                 explorer_id,
                 resource,
-            } => {
-                let mut p = Payload::new();
-                p.insert("type".to_string(), "GenerateResourceResponse".to_string());
-                p.insert("ResourceRequested".to_string(), format!("{:?}", resource));
-                p.insert(
-                    "rocketStrategy".to_string(),
-                    self.rocket_strategy.to_string(),
-                );
-
-                let mut log = LogEvent::new(
-                    Some(Participant::new(ActorType::Planet, state.id())),
-                    Some(Participant::new(ActorType::Explorer, explorer_id)),
-                    EventType::MessagePlanetToExplorer,
-                    Channel::Debug,
-                    Payload::new(),
-                );
-
-                if self.rocket_strategy == RocketStrategy::EmergencyReserve
-                    && self.charged_count(state) <= 1
-                {
+            } => self
+                .generate_one(state, generator, explorer_id, resource)
+                .ok()
+                .map(|resource| PlanetToExplorer::GenerateResourceResponse { resource }),
+            ExplorerToPlanet::CombineResourceRequest { msg, .. } => {
+                self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Warning, || {
+                    let mut p = Payload::new();
+                    p.insert("type".to_string(), "CombineResourceResponse".to_string());
+                    p.insert("ResourceRequested".to_string(), format!("{:?}", msg));
                     p.insert(
-                        "energyCellCount".to_string(),
-                        format!("{} , this is intended behavior", self.charged_count(state)),
+                        "rocketStrategy".to_string(),
+                        self.rocket_strategy.to_string(),
                     );
                     p.insert("Result".to_string(), "Failure".to_string());
-                    log.payload = p;
-                    log.emit();
-                    return None;
-                }
-                let Some((cell, _)) = state.full_cell() else {
-                    p.insert("Result".to_string(), "Failure".to_string());
-                    log.payload = p;
-                    log.emit();
-                    return None;
-                };
-                //1- check the planet internal resource
-                match self.basic_resource {
-                    //2- check if the explorer is in fact asking for that one
-                    BasicResourceType::Oxygen => match resource {
-                        BasicResourceType::Oxygen => {
-                            let new_basic_resource =
-                                generator.make_oxygen(cell).ok().map(BasicResource::Oxygen);
-
-                            p.insert("Result".to_string(), "Success".to_string());
-                            log.payload = p;
-                            log.emit();
-
-                            Some(PlanetToExplorer::GenerateResourceResponse {
-                                resource: new_basic_resource,
-                            })
-                        }
-
-                        _ => {
-                            p.insert("Result".to_string(), "Failure".to_string());
-                            log.payload = p;
-                            log.channel = Channel::Warning;
-                            log.emit();
-                            None
-                        }
-                    },
-                    BasicResourceType::Hydrogen => match resource {
-                        BasicResourceType::Hydrogen => {
-                            let new_basic_resource = generator
-                                .make_hydrogen(cell)
-                                .ok()
-                                .map(BasicResource::Hydrogen);
-
-                            p.insert("Result".to_string(), "Success".to_string());
-                            log.payload = p;
-                            log.emit();
-
-                            Some(PlanetToExplorer::GenerateResourceResponse {
-                                resource: new_basic_resource,
-                            })
-                        }
-
-                        _ => {
-                            p.insert("Result".to_string(), "Failure".to_string());
-                            log.payload = p;
-                            log.channel = Channel::Warning;
-                            log.emit();
-                            None
-                        }
-                    },
-                    BasicResourceType::Carbon => match resource {
-                        BasicResourceType::Carbon => {
-                            let new_basic_resource =
-                                generator.make_carbon(cell).ok().map(BasicResource::Carbon);
-
-                            p.insert("Result".to_string(), "Success".to_string());
-                            log.payload = p;
-                            log.emit();
-
-                            Some(PlanetToExplorer::GenerateResourceResponse {
-                                resource: new_basic_resource,
-                            })
-                        }
-
-                        _ => {
-                            p.insert("Result".to_string(), "Failure".to_string());
-                            log.payload = p;
-                            log.channel = Channel::Warning;
-                            log.emit();
-                            None
-                        }
-                    },
-                    BasicResourceType::Silicon => match resource {
-                        BasicResourceType::Silicon => {
-                            let new_basic_resource = generator
-                                .make_silicon(cell)
-                                .ok()
-                                .map(BasicResource::Silicon);
-
-                            p.insert("Result".to_string(), "Success".to_string());
-                            log.payload = p;
-                            log.emit();
-
-                            Some(PlanetToExplorer::GenerateResourceResponse {
-                                resource: new_basic_resource,
-                            })
-                        }
-
-                        _ => {
-                            p.insert("Result".to_string(), "Failure".to_string());
-                            log.payload = p;
-                            log.channel = Channel::Warning;
-                            log.emit();
-                            None
-                        }
-                    },
-                }
-            }
-            ExplorerToPlanet::CombineResourceRequest { explorer_id, msg } => {
-                let mut p = Payload::new();
-                p.insert("type".to_string(), "CombineResourceResponse".to_string());
-                p.insert("ResourceRequested".to_string(), format!("{:?}", msg));
-                p.insert(
-                    "rocketStrategy".to_string(),
-                    self.rocket_strategy.to_string(),
-                );
-                p.insert("Result".to_string(), "Failure".to_string());
-                let log = LogEvent::new(
-                    Some(Participant::new(ActorType::Planet, state.id())),
-                    Some(Participant::new(ActorType::Explorer, explorer_id)),
-                    EventType::MessagePlanetToExplorer,
-                    Channel::Warning,
-                    p,
-                );
-                log.emit();
+                    p
+                });
 
                 None //type C doesn't combine
 
@@ -729,150 +1155,1258 @@ impl PlanetAI for PlanetCoreThinkingModel {
                 //         }
                 //     }
             }
-            ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id } => {
-                let count = self.charged_count(state) ;
+            ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id } => {
+                // An explorer the service tier would refuse sees no free cell.
+                let tier = self.service_tier(state);
+                let mut free = if self.serves(tier, explorer_id) {
+                    self.unreserved_count(state)
+                } else {
+                    0
+                };
+                let budget_left = self.budget_left(state);
+                if let Some(left) = budget_left {
+                    free = free.min(left.saturating_sub(self.reservations.total()));
+                }
+                // With reservations enabled the reported cells are set aside
+                // for this explorer until they are used or expire.
+                let available_cells = match &self.reservation_policy {
+                    Some(policy) => {
+                        let held = self.reservations.held_by(explorer_id);
+                        // Rockets may have used up cells since the reservation was granted
+                        let granted = (held + free)
+                            .min(policy.max_cells)
+                            .min(self.available_to(state, explorer_id));
+                        self.reservations
+                            .reserve(explorer_id, granted, self.tick + policy.ttl);
+                        granted
+                    }
+                    None => free,
+                };
+
+                self.log_lazy(planet_id, explorer, EventType::MessagePlanetToExplorer, Channel::Trace, || {
+                    let mut p = Payload::new();
+                    p.insert("type".to_string(), "AvailableEnergyCellResponse".to_string());
+                    p.insert(
+                        "internalEnergyCellCount".to_string(),
+                        format!("{:?}", self.charged_count(state)),
+                    );
+                    p.insert(
+                        "rocketStrategy".to_string(),
+                        self.rocket_strategy.to_string(),
+                    );
+                    p.insert("sentEnergyCellCount".to_string(), format!("{:?}", available_cells));
+                    if let Some(tier) = tier {
+                        p.insert("serviceTier".to_string(), format!("{:?}", tier));
+                    }
+                    if let Some(left) = budget_left {
+                        p.insert("budgetLeft".to_string(), left.to_string());
+                    }
+                    p.insert(
+                        "reservedCells".to_string(),
+                        self.reservations.total().to_string(),
+                    );
+                    p.insert("Result".to_string(), "Failure".to_string());
+                    p
+                });
+
+                Some(PlanetToExplorer::AvailableEnergyCellResponse { available_cells })
+            }
+        }
+    }
+}
+
+// The runtime only knows about `PlanetState`; the handlers above are generic so
+// the same logic can also run on a `SimulatedPlanetState`.
+impl PlanetAI for PlanetCoreThinkingModel {
+    // fn handle_orchestrator_msg(
+    //     &mut self,
+    //     state: &mut PlanetState,
+    //     _generator: &Generator,
+    //     _combinator: &Combinator,
+    //     msg: OrchestratorToPlanet,
+    // ) -> Option<PlanetToOrchestrator> {
+    //     match msg {
+    //         OrchestratorToPlanet::Sunray(sunray) => {
+    //             // let mut p = Payload::new();
+    //             // p.insert("type".to_string(), "SunrayAck".to_string());
+    //             // p.insert(
+    //             //     "rocketStrategy".to_string(),
+    //             //     self.rocket_strategy.to_string(),
+    //             // );
+    //             // p.insert(
+    //             //     "energyCellCountBeforeAck".to_string(),
+    //             //     format!("{}", self.charged_count(state)),
+    //             // );
+    //             // p.insert(
+    //             //     "rocketBeforeAck".to_string(),
+    //             //     format!("{}", state.has_rocket()),
+    //             // );
+    //             // let mut log = LogEvent::new(
+    //             //     ActorType::Planet,
+    //             //     state.id(),
+    //             //     ActorType::Orchestrator,
+    //             //     0u32.to_string(),
+    //             //     EventType::MessagePlanetToOrchestrator,
+    //             //     Channel::Debug,
+    //             //     Payload::new(), //fake payload
+    //             // );
+    //             //
+    //             // // Try to charge an empty cell
+    //             // let leftover = state.charge_cell(sunray);
+    //             //
+    //             // // Helper: check if this strategy allows building
+    //             // let can_build = |strategy: &RocketStrategy| -> bool {
+    //             //     match strategy {
+    //             //         RocketStrategy::Disabled => false,
+    //             //         RocketStrategy::Default => false, // never build on Sunray
+    //             //         RocketStrategy::Safe => true,
+    //             //         RocketStrategy::EmergencyReserve => true,
+    //             //     }
+    //             // };
+    //             //
+    //             // // CASE A — leftover == None  → at least one cell was uncharged
+    //             // if leftover.is_none() {
+    //             //     // Should we try building a rocket now?
+    //             //     if state.can_have_rocket()
+    //             //         && !state.has_rocket()
+    //             //         && can_build(&self.rocket_strategy)
+    //             //     {
+    //             //         let _ = try_build_rocket(state);
+    //             //     }
+    //             // } else {
+    //             //     // CASE B — leftover == Some(sunray) → all cells were full
+    //             //     if state.can_have_rocket()
+    //             //         && !state.has_rocket()
+    //             //         && can_build(&self.rocket_strategy)
+    //             //     {
+    //             //         if let Some(cell_index) = try_build_rocket(state) {
+    //             //             // Recharge the cell used to build the rocket with the leftover sunray
+    //             //             state.cell_mut(cell_index).charge(leftover.unwrap());
+    //             //         }
+    //             //     }
+    //             // }
+    //             //
+    //             // p.insert(
+    //             //     "energyCellCountAfterAck".to_string(),
+    //             //     format!("{}", self.charged_count(state)),
+    //             // );
+    //             // p.insert(
+    //             //     "rocketAfterAck".to_string(),
+    //             //     format!("{}", state.has_rocket()),
+    //             // );
+    //             //
+    //             // log.payload = p;
+    //             // log.emit();
+    //             //
+    //             // Some(PlanetToOrchestrator::SunrayAck {
+    //             //     planet_id: state.id(),
+    //             // })
+    //         }
+    //         // OrchestratorToPlanet::InternalStateRequest { .. } => match self.rocket_strategy {
+    //         //     RocketStrategy::EmergencyReserve => {
+    //         //         let mut dummy_state = PlanetState::to_dummy(state);
+    //         //
+    //         //         let mut p = Payload::new();
+    //         //         p.insert("type".to_string(), "InternalStateResponse".to_string());
+    //         //         p.insert(
+    //         //             "internalDummyState".to_string(),
+    //         //             format!("{:?}", dummy_state.clone()),
+    //         //         );
+    //         //         let mut log = LogEvent::new(
+    //         //             ActorType::Planet,
+    //         //             state.id(),
+    //         //             ActorType::Orchestrator,
+    //         //             0u32.to_string(),
+    //         //             EventType::MessagePlanetToOrchestrator,
+    //         //             Channel::Trace,
+    //         //             Payload::new(), //fake payload
+    //         //         );
+    //         //
+    //         //         dummy_state.charged_cells_count =
+    //         //             dummy_state.charged_cells_count.saturating_sub(1);
+    //         //
+    //         //         p.insert("sentDummyState".to_string(), format!("{:?}", dummy_state));
+    //         //         log.payload = p;
+    //         //         log.emit();
+    //         //
+    //         //         Some(PlanetToOrchestrator::InternalStateResponse {
+    //         //             planet_id: state.id(),
+    //         //             planet_state: dummy_state,
+    //         //         })
+    //         //     }
+    //         //     _ => {
+    //         //         let mut p = Payload::new();
+    //         //         p.insert("type".to_string(), "InternalStateResponse".to_string());
+    //         //         p.insert(
+    //         //             "DummyState".to_string(),
+    //         //             format!("{:?}", PlanetState::to_dummy(state)),
+    //         //         );
+    //         //         let log = LogEvent::new(
+    //         //             ActorType::Planet,
+    //         //             state.id(),
+    //         //             ActorType::Orchestrator,
+    //         //             0u32.to_string(),
+    //         //             EventType::MessagePlanetToOrchestrator,
+    //         //             Channel::Trace,
+    //         //             p,
+    //         //         );
+    //         //         log.emit();
+    //         //
+    //         //         Some(PlanetToOrchestrator::InternalStateResponse {
+    //         //             planet_id: state.id(),
+    //         //             planet_state: PlanetState::to_dummy(state),
+    //         //         })
+    //         //     }
+    //         // },
+    //         //OrchestratorToPlanet::Asteroid(_) => {}//handle_asteroid
+    //         // OrchestratorToPlanet::StartPlanetAI(_) => {}//start
+    //         // OrchestratorToPlanet::StopPlanetAI(_) => {}//stop
+    //         _ => None,
+    //     }
+    // }
+
+    fn handle_sunray(&mut self, state: &mut PlanetState, generator: &Generator, combinator: &Combinator, sunray: Sunray) {
+        PlanetCoreThinkingModel::handle_sunray(self, state, generator, combinator, sunray)
+    }
+
+    fn handle_asteroid(&mut self, state: &mut PlanetState, generator: &Generator, combinator: &Combinator) -> Option<Rocket> {
+        PlanetCoreThinkingModel::handle_asteroid(self, state, generator, combinator)
+    }
+
+    fn handle_internal_state_req(&mut self, state: &mut PlanetState, generator: &Generator, combinator: &Combinator) -> DummyPlanetState {
+        PlanetCoreThinkingModel::handle_internal_state_req(self, state, generator, combinator)
+    }
+
+    fn handle_explorer_msg(&mut self, state: &mut PlanetState, generator: &Generator, combinator: &Combinator, msg: ExplorerToPlanet) -> Option<PlanetToExplorer> {
+        PlanetCoreThinkingModel::handle_explorer_msg(self, state, generator, combinator, msg)
+    }
+
+    fn on_explorer_arrival(&mut self, state: &mut PlanetState, generator: &Generator, combinator: &Combinator, explorer_id: u32) {
+        PlanetCoreThinkingModel::on_explorer_arrival(self, state, generator, combinator, explorer_id)
+    }
+
+    fn on_explorer_departure(&mut self, state: &mut PlanetState, generator: &Generator, combinator: &Combinator, explorer_id: u32) {
+        PlanetCoreThinkingModel::on_explorer_departure(self, state, generator, combinator, explorer_id)
+    }
+
+    fn on_start(&mut self, state: &PlanetState, generator: &Generator, combinator: &Combinator) {
+        PlanetCoreThinkingModel::on_start(self, state, generator, combinator)
+    }
+
+    fn on_stop(&mut self, state: &PlanetState, generator: &Generator, combinator: &Combinator) {
+        PlanetCoreThinkingModel::on_stop(self, state, generator, combinator)
+    }
+}
+
+/// Tries to build a rocket using the fully charged energy cell chosen by
+/// `cells`. Returns `Some(index)` on success, or `None` on failure.
+///
+/// If no full cell exists, all the charged cells are `reserved` for explorers,
+/// or the rocket cannot be built, the function returns `None`.
+fn try_build_rocket<S: PlanetCells>(state: &mut S, reserved: u32, cells: &mut CellSelector) -> Option<usize> {
+    let charged = state.cells_iter().filter(|c| c.is_charged()).count() as u32;
+    if charged <= reserved {
+        return None;
+    }
+    let cell_index = cells.pick(state, Purpose::Rocket)?;
+    state.build_rocket(cell_index).ok()?; // if Err -> return None
+
+    Some(cell_index)
+}
+
+/// Creates and initializes a new `Planet` instance with a predefined set of
+/// generation and combination rules, a basic AI model, and the communication
+/// channels used to interact with the orchestrator and explorers.
+///
+/// Planet configuration, the defaults of [`PlanetConfig::new`]
+/// - Type: A
+/// - Generation rule: `basic_resource`, Hydrogen if `None`
+/// - Combination rules: none
+///
+/// Parameters
+/// - The channels used to receive messages from the orchestrator and
+///   send responses back
+/// - The channel used to receive messages from explorers
+/// - planet_id: the id of the planet
+/// - rocket_strategy: takes a `RocketStrategy`, an Enum containing:
+///     - Disabled: do not generate rockets under any condition.
+///     - Default: generate a rocket only when an asteroid is coming.
+///     - Safe: always rebuild a rocket when there isn't any
+///     - EmergencyReserve: same as `Safe`, but preserves one fully charged cell for emergencies.
+///     - Stockpile: same as `Safe`, but keeps charged cells to rebuild up to
+///       three rockets in a row.
+///     - Planner: weighs survival against explorer revenue at every decision.
+/// - basic_resource: takes an `Option<BasicResourceType>` and set that one as a basic resource for the planet
+///
+/// Returns:
+/// - `Ok(Planet)` if the configuration is valid for the selected planet type
+/// - `Err(String)` if the rules exceed the constraints of the planet type
+pub fn houston_we_have_a_borrow(
+    rx_orchestrator: Receiver<OrchestratorToPlanet>,
+    tx_orchestrator: Sender<PlanetToOrchestrator>,
+    rx_explorer: Receiver<ExplorerToPlanet>,
+    planet_id: u32,
+    rocket_strategy: RocketStrategy,
+    basic_resource: Option<BasicResourceType>,
+) -> Result<Planet, String> {
+    create_planet(
+        PlanetConfig::new(planet_id, rocket_strategy, basic_resource),
+        rx_orchestrator,
+        tx_orchestrator,
+        rx_explorer,
+    )
+}
+
+/// Same as [`houston_we_have_a_borrow`], but takes every construction
+/// parameter from a [`PlanetConfig`].
+///
+/// On these raw channels an explorer rejected by the [`ExplorerPolicy`] still
+/// gets `IncomingExplorerResponse { res: Ok(()) }`, which the `common_game`
+/// runtime sends itself; the reason is only in the `ExplorerAdmission` log event.
+/// [`PlanetHandle::incoming_explorer`] fails with [`PlanetError::Rejected`]
+/// and the reason instead.
+pub fn create_planet(
+    config: PlanetConfig,
+    rx_orchestrator: Receiver<OrchestratorToPlanet>,
+    tx_orchestrator: Sender<PlanetToOrchestrator>,
+    rx_explorer: Receiver<ExplorerToPlanet>,
+) -> Result<Planet, String> {
+    create_planet_with_reporter(config, rx_orchestrator, tx_orchestrator, rx_explorer)
+        .map(|(planet, _)| planet)
+}
+
+/// Same as [`create_planet`], with the configuration loaded from a TOML or
+/// JSON file by [`PlanetConfig::load`].
+#[cfg(feature = "serde")]
+pub fn create_planet_from_file(
+    path: impl AsRef<std::path::Path>,
+    rx_orchestrator: Receiver<OrchestratorToPlanet>,
+    tx_orchestrator: Sender<PlanetToOrchestrator>,
+    rx_explorer: Receiver<ExplorerToPlanet>,
+) -> Result<Planet, String> {
+    let config = PlanetConfig::load(path).map_err(|e| e.to_string())?;
+    create_planet(config, rx_orchestrator, tx_orchestrator, rx_explorer)
+}
+
+/// [`create_planet`], also returning the [`Reporter`] that builds the final
+/// report once `Planet::run` has returned.
+pub(crate) fn create_planet_with_reporter(
+    config: PlanetConfig,
+    rx_orchestrator: Receiver<OrchestratorToPlanet>,
+    tx_orchestrator: Sender<PlanetToOrchestrator>,
+    rx_explorer: Receiver<ExplorerToPlanet>,
+) -> Result<(Planet, Reporter), String> {
+    let ai = PlanetCoreThinkingModel::new(&config);
+    let reporter = ai.reporter();
+    ai.log_creation(&config);
+    build_planet(&config, Box::new(ai), rx_orchestrator, tx_orchestrator, rx_explorer)
+        .map(|planet| (planet, reporter))
+}
+
+/// `Planet::new` with the generation and combination rules of `config`, which
+/// it checks against the planet type.
+pub(crate) fn build_planet(
+    config: &PlanetConfig,
+    ai: Box<dyn PlanetAI>,
+    rx_orchestrator: Receiver<OrchestratorToPlanet>,
+    tx_orchestrator: Sender<PlanetToOrchestrator>,
+    rx_explorer: Receiver<ExplorerToPlanet>,
+) -> Result<Planet, String> {
+    let gen_rules = if let Some(b_res) = config.basic_resource {
+        vec![b_res]
+    } else {
+        vec![
+            // BasicResourceType::Oxygen,
+            BasicResourceType::Hydrogen,
+            // BasicResourceType::Carbon,
+            // BasicResourceType::Silicon,
+        ]
+    };
+
+    let comb_rules = config.combination_rules.clone();
+
+    Planet::new(
+        config.planet_id,
+        config.planet_type,
+        ai,
+        gen_rules,
+        comb_rules,
+        (rx_orchestrator, tx_orchestrator),
+        rx_explorer,
+    )
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    // --- Test Harness ---
+    // The AI runs synchronously on a simulated state: every call returns what
+    // the planet would have sent back, no thread or channel involved.
+    fn simulated_planet(strategy: RocketStrategy, resource: BasicResourceType) -> SimulatedPlanet {
+        simulated_configured_planet(PlanetConfig::new(1, strategy, Some(resource)))
+    }
+
+    fn simulated_configured_planet(config: PlanetConfig) -> SimulatedPlanet {
+        SimulatedPlanet::new(config).expect("Failed to create planet instance")
+    }
+
+    fn available_cells(planet: &mut SimulatedPlanet, explorer_id: u32) -> u32 {
+        match planet.explorer_msg(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id }) {
+            Some(PlanetToExplorer::AvailableEnergyCellResponse { available_cells }) => available_cells,
+            _ => panic!("Unexpected response to AvailableEnergyCellRequest"),
+        }
+    }
+
+    fn generate(planet: &mut SimulatedPlanet, explorer_id: u32, resource: BasicResourceType) -> bool {
+        matches!(
+            planet.explorer_msg(ExplorerToPlanet::GenerateResourceRequest { explorer_id, resource }),
+            Some(PlanetToExplorer::GenerateResourceResponse { resource: Some(_) })
+        )
+    }
+
+    // ==========================================
+    // TESTS
+    // ==========================================
+
+    // The original strategy tests, run on the simulated planet; the features
+    // added since have their own modules below.
+
+    #[test]
+    fn test_strategy_safe_builds_rocket_immediately() {
+        // SCENARIO: Safe strategy should build a rocket immediately after receiving energy.
+        let mut planet = simulated_planet(RocketStrategy::Safe, BasicResourceType::Hydrogen);
+
+        planet.sunray();
+
+        // The 'Safe' strategy logic is: If I have energy, make a rocket.
+        assert!(planet.internal_state().has_rocket, "Safe strategy failed to build rocket immediately");
+    }
+
+    #[test]
+    fn test_strategy_default_waits_for_asteroid() {
+        // SCENARIO: Default strategy keeps energy stored and only builds when threatened.
+        let mut planet = simulated_planet(RocketStrategy::Default, BasicResourceType::Hydrogen);
+
+        // 1. Send Sunray
+        planet.sunray();
+
+        // 2. Verify NO Rocket yet
+        let planet_state = planet.internal_state();
+        assert!(!planet_state.has_rocket, "Default strategy built rocket too early!");
+        assert!(planet_state.charged_cells_count > 0, "Default strategy lost the energy!");
+
+        // 3. Send Asteroid, expect Rocket Launch
+        assert!(planet.asteroid().is_some(), "Default strategy failed to build rocket for asteroid");
+    }
+
+    #[test]
+    fn test_emergency_reserve_deception() {
+        // SCENARIO: EmergencyReserve keeps 1 cell hidden.
+        // If we only give it 1 Sunray, it should claim to be empty.
+        let mut planet = simulated_planet(RocketStrategy::EmergencyReserve, BasicResourceType::Hydrogen);
+
+        // 1. Charge exactly 1 cell
+        planet.sunray();
+
+        // 2. Check Orchestrator Report (The "Lie"): real state is 1, but logic subtracts 1.
+        assert_eq!(planet.internal_state().charged_cells_count, 0, "EmergencyReserve failed to hide the reserve cell from Orchestrator");
+
+        // 3. Check Explorer Availability (The "Denial")
+        assert_eq!(available_cells(&mut planet, 99), 0, "EmergencyReserve failed to hide reserve cell from Explorer");
+
+        // 4. Verify Resource Generation fails
+        assert!(!generate(&mut planet, 99, BasicResourceType::Hydrogen), "Planet should not generate resources using the emergency reserve");
+    }
+
+    #[test]
+    fn test_resource_generation_match() {
+        // SCENARIO: Planet is set to produce Oxygen. Request Oxygen (Success) then Carbon (Failure).
+        let mut planet = simulated_planet(RocketStrategy::Default, BasicResourceType::Oxygen);
+
+        // 1. Charge Up, request Correct Resource (Oxygen)
+        planet.sunray();
+        assert!(generate(&mut planet, 99, BasicResourceType::Oxygen), "Failed to generate correct resource");
+
+        // 2. Recharge, request Incorrect Resource (Carbon)
+        planet.sunray();
+        assert!(!generate(&mut planet, 99, BasicResourceType::Carbon), "Planet generated a resource it does not support!");
+        assert_eq!(planet.state.charged_count(), 1, "A refused request should not spend energy");
+    }
+
+    #[test]
+    fn test_safe_strategy_rapid_reload() {
+        // SCENARIO: 'Safe' strategy has a rocket AND extra energy.
+        // When it fires the rocket at an asteroid, it should immediately build a NEW one using the spare energy.
+        let mut planet = simulated_planet(RocketStrategy::Safe, BasicResourceType::Hydrogen);
+
+        // 1. Charge TWICE (1st -> builds rocket, 2nd -> stored as spare energy)
+        planet.sunray();
+        planet.sunray();
+
+        // 2. Verify State before attack: Has Rocket + 1 Spare Cell
+        let planet_state = planet.internal_state();
+        assert!(planet_state.has_rocket, "Should have initial rocket");
+        assert_eq!(planet_state.charged_cells_count, 1);
+
+        // 3. Send Asteroid (Forces launch)
+        assert!(planet.asteroid().is_some(), "Planet should have fired");
+
+        // 4. CRITICAL CHECK: Did it reload?
+        assert!(planet.internal_state().has_rocket, "Safe strategy failed to auto-reload rocket using spare energy!");
+    }
+
+    #[test]
+    fn test_strategy_disabled_is_defenseless() {
+        // SCENARIO: 'Disabled' strategy should accumulate energy but NEVER build a rocket,
+        // even if an asteroid is about to kill it.
+        let mut planet = simulated_planet(RocketStrategy::Disabled, BasicResourceType::Hydrogen);
+
+        // 1. Charge up (Disabled strategy should just store this as raw energy)
+        planet.sunray();
+
+        // 2. Verify Internal State (Energy: Yes, Rocket: No)
+        let planet_state = planet.internal_state();
+        assert!(!planet_state.has_rocket, "Disabled strategy should not build rocket");
+        assert!(planet_state.charged_cells_count > 0, "Disabled strategy should store energy");
+
+        // 3. Send Asteroid, expect NO Rocket (Planet Doom)
+        assert!(planet.asteroid().is_none(), "Disabled strategy should NOT fire a rocket, even in emergency");
+    }
+
+    #[test]
+    fn test_emergency_reserve_allows_surplus_use() {
+        // SCENARIO: Planet has received 3 Cells. Reserve is 1. 1 becomes a rocket
+        // It should allow generating 1 resource (dropping to 1 cell), then REFUSE the next request.
+        let mut planet = simulated_planet(RocketStrategy::EmergencyReserve, BasicResourceType::Hydrogen);
+
+        // 1. Charge TRICE (Total 3 cells)
+        planet.sunray();
+        planet.sunray();
+        planet.sunray();
+
+        // 2. Explorer requests Availability: Actual 2, Reserve 1 -> Reports 1
+        assert_eq!(available_cells(&mut planet, 99), 1, "Should report surplus cells only (Actual 2 - Reserve 1 = 1)");
+
+        // 3. Generate Resource (Should SUCCEED using the surplus cell)
+        assert!(generate(&mut planet, 99, BasicResourceType::Hydrogen), "Should succeed with surplus");
+
+        // 4. Verify we are now down to the Reserve: the AI "lies" and says 0
+        assert_eq!(planet.internal_state().charged_cells_count, 0, "Should now report 0 (masking the last reserve cell)");
+
+        // 5. Try to Generate AGAIN (Should FAIL)
+        assert!(!generate(&mut planet, 99, BasicResourceType::Hydrogen), "Should refuse to use the last emergency cell");
+    }
+
+    // Explorer energy cell reservations.
+    mod reservations {
+        use super::*;
+
+        #[test]
+        fn test_reservation_hides_cells_from_other_explorers() {
+            // SCENARIO: Explorer 99 asks for cells and gets the only one reserved.
+            // Explorer 98 must see nothing and be refused, while 99 can still spend its cell.
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_reservation(ReservationPolicy::default());
+            let mut planet = simulated_configured_planet(config);
+
+            planet.sunray();
+
+            assert_eq!(available_cells(&mut planet, 99), 1);
+            assert_eq!(available_cells(&mut planet, 98), 0);
+            assert!(!generate(&mut planet, 98, BasicResourceType::Hydrogen), "Reserved cell was given to another explorer");
+            assert!(generate(&mut planet, 99, BasicResourceType::Hydrogen), "Reservation holder should be served");
+        }
+
+        #[test]
+        fn test_reservation_expires() {
+            // SCENARIO: A reservation that is not used within its ttl goes back to the pool.
+            let policy = ReservationPolicy { ttl: 2, ..ReservationPolicy::default() };
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_reservation(policy);
+            let mut planet = simulated_configured_planet(config);
+
+            planet.sunray();
+            assert_eq!(available_cells(&mut planet, 99), 1);
+
+            // Two events later the reservation is gone
+            planet.internal_state();
+            assert_eq!(available_cells(&mut planet, 98), 1, "Expired reservation was not released");
+        }
+
+        #[test]
+        fn test_reserved_cells_are_not_turned_into_rockets() {
+            // SCENARIO: 'Safe' wants to rebuild after firing, but the only charged cell is reserved.
+            let config = PlanetConfig::new(1, RocketStrategy::Safe, Some(BasicResourceType::Hydrogen))
+                .with_reservation(ReservationPolicy::default());
+            let mut planet = simulated_configured_planet(config);
+
+            // 1. First sunray becomes the rocket, second one is stored
+            planet.sunray();
+            planet.sunray();
+
+            // 2. Explorer reserves the stored cell
+            assert_eq!(available_cells(&mut planet, 99), 1);
+
+            // 3. Asteroid: fire the rocket, but do not rebuild from the reserved cell
+            assert!(planet.asteroid().is_some());
+            let planet_state = planet.internal_state();
+            assert!(!planet_state.has_rocket, "Rocket was built from a reserved cell");
+            assert_eq!(planet_state.charged_cells_count, 1);
+        }
+
+        #[test]
+        fn test_reservation_does_not_take_the_emergency_cell() {
+            // SCENARIO: 'EmergencyReserve' rebuilds its rocket after firing, leaving only the
+            // emergency cell; an explorer reserved a cell earlier but can't have that one.
+            let config = PlanetConfig::new(1, RocketStrategy::EmergencyReserve, Some(BasicResourceType::Hydrogen))
+                .with_reservation(ReservationPolicy::default());
+            let mut planet = simulated_configured_planet(config);
+
+            // 1. A rocket and two charged cells, one of them the emergency cell
+            for _ in 0..3 {
+                planet.sunray();
+            }
+            assert_eq!(available_cells(&mut planet, 99), 1);
+
+            // 2. Fire and rebuild: one cell left
+            assert!(planet.asteroid().is_some());
+            assert!(planet.state.has_rocket());
+            assert_eq!(planet.state.charged_count(), 1);
+
+            // 3. The reservation does not reach the emergency cell, and shrinks
+            assert!(!generate(&mut planet, 99, BasicResourceType::Hydrogen));
+            assert_eq!(available_cells(&mut planet, 99), 0);
+            assert_eq!(planet.state.charged_count(), 1);
+        }
+    }
+
+    // Per-planet log sinks, capture helpers and verbosity.
+    mod log_sinks {
+        use super::*;
+
+        #[test]
+        fn test_events_go_to_the_configured_sink() {
+            // SCENARIO: A planet with its own sink logs there, creation event included.
+            let sink = RingBufferSink::new(64);
+            let config = PlanetConfig::new(1, RocketStrategy::Default, Some(BasicResourceType::Hydrogen))
+                .with_log_sink(sink.clone());
+            let mut planet = simulated_configured_planet(config);
+            let after_creation = sink.events().len();
+            assert_eq!(after_creation, 1, "Expected the Creation event alone");
+
+            planet.sunray();
+            let events = sink.events();
+            assert!(events.len() > after_creation, "Sunray events were not captured");
+            assert!(events.iter().any(|e| matches!(e.event_type, EventType::MessagePlanetToOrchestrator)));
+        }
+
+        #[test]
+        fn test_unsupported_resource_is_logged_as_failure() {
+            // SCENARIO: The refusal of an unsupported resource is visible in the logs too.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Default, Some(BasicResourceType::Oxygen))
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+
+            planet.sunray();
+            assert!(!generate(&mut planet, 99, BasicResourceType::Carbon));
+
+            let responses = capture.with_payload("type", "GenerateResourceResponse");
+            assert_eq!(responses.len(), 1);
+            assert_eq!(responses[0].payload["Result"], "Failure");
+            assert_eq!(responses[0].payload["ResourceRequested"], "Carbon");
+            assert_eq!(responses[0].channel, Channel::Warning);
+            assert_eq!(capture.by_event_type(&EventType::MessagePlanetToExplorer).len(), 1);
+        }
+
+        #[test]
+        fn test_verbosity_drops_sunray_chatter_but_keeps_failures() {
+            // SCENARIO: Orchestrator chatter is turned down, explorer failures still get through.
+            let capture = CaptureSink::for_planet(1);
+            let verbosity = LogVerbosity::new(Channel::Info)
+                .with_event(EventType::MessagePlanetToExplorer, Channel::Warning);
+            let config = PlanetConfig::new(1, RocketStrategy::Default, Some(BasicResourceType::Oxygen))
+                .with_log_sink(capture.clone())
+                .with_verbosity(verbosity);
+            let mut planet = simulated_configured_planet(config);
+
+            planet.sunray();
+            planet.internal_state();
+            assert!(generate(&mut planet, 99, BasicResourceType::Oxygen));
+            planet.sunray();
+            assert!(!generate(&mut planet, 99, BasicResourceType::Carbon));
+
+            assert!(capture.with_payload("type", "SunrayAck").is_empty());
+            assert!(capture.with_payload("type", "InternalStateResponse").is_empty());
+            let responses = capture.with_payload("type", "GenerateResourceResponse");
+            assert_eq!(responses.len(), 1, "Only the failure should be logged");
+            assert_eq!(responses[0].payload["Result"], "Failure");
+            assert_eq!(capture.with_payload("type", "Creation").len(), 1);
+        }
+    }
+
+    // The final report.
+    mod final_report {
+        use super::*;
+
+        #[test]
+        fn test_final_report() {
+            // SCENARIO: The report sums up the planet life and is logged as well.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Safe, Some(BasicResourceType::Carbon))
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+
+            planet.sunray();
+            planet.sunray();
+            assert!(generate(&mut planet, 7, BasicResourceType::Carbon));
+            assert!(!generate(&mut planet, 8, BasicResourceType::Carbon), "No cell left");
+            assert_eq!(available_cells(&mut planet, 8), 0);
+            assert!(planet.asteroid().is_some());
+
+            let report = planet.final_report();
+            assert_eq!(report.sunrays, 2);
+            assert_eq!(report.rockets_built, 1);
+            assert_eq!((report.asteroids, report.asteroids_deflected), (1, 1));
+            assert_eq!((report.explorer_requests, report.resources_generated), (3, 1));
+            assert_eq!(report.explorers_served, 2);
+            assert_eq!(report.events_handled(), 6);
+            assert_eq!((report.charged_cells, report.total_cells, report.has_rocket), (0, 5, false));
+
+            let logged = capture.with_payload("type", "FinalReport");
+            assert_eq!(logged.len(), 1);
+            assert_eq!(logged[0].payload["resourcesGenerated"], "1");
+        }
+    }
+
+    // AI start and stop.
+    mod lifecycle {
+        use super::*;
+
+        #[test]
+        fn test_safe_rebuilds_rocket_on_restart() {
+            // SCENARIO: 'Safe' could not rebuild after firing because its cell was reserved.
+            // Once the reservation is gone, a restart brings the rocket back.
+            let policy = ReservationPolicy { ttl: 2, ..ReservationPolicy::default() };
+            let config = PlanetConfig::new(1, RocketStrategy::Safe, Some(BasicResourceType::Hydrogen))
+                .with_reservation(policy);
+            let mut planet = simulated_configured_planet(config);
+            planet.start();
+
+            planet.sunray();
+            planet.sunray();
+            assert_eq!(available_cells(&mut planet, 99), 1);
+            assert!(planet.asteroid().is_some());
+
+            let planet_state = planet.internal_state();
+            assert!(!planet_state.has_rocket, "Rocket was built from a reserved cell");
+            assert_eq!(planet_state.charged_cells_count, 1, "Reservation should have expired");
+
+            planet.stop();
+            assert!(!planet.ai.is_running());
+            planet.start();
+            assert!(planet.internal_state().has_rocket, "Restart should rebuild the rocket");
+        }
+
+        #[test]
+        fn test_session_counters() {
+            // SCENARIO: Each stop logs the session counters, which restart from zero only if configured.
+            for (session_counters, second_session) in [(SessionCounters::Preserve, "2"), (SessionCounters::ResetOnStart, "1")] {
+                let capture = CaptureSink::for_planet(1);
+                let config = PlanetConfig::new(1, RocketStrategy::Default, Some(BasicResourceType::Hydrogen))
+                    .with_log_sink(capture.clone())
+                    .with_session_counters(session_counters);
+                let mut planet = simulated_configured_planet(config);
+
+                for _ in 0..2 {
+                    planet.start();
+                    planet.sunray();
+                    planet.stop();
+                }
+
+                let stops = capture.with_payload("type", "StopAI");
+                assert_eq!(stops.len(), 2);
+                assert_eq!(stops[0].payload["sunrays"], "1");
+                assert_eq!(stops[1].payload["sunrays"], second_session);
+                assert_eq!(stops[1].payload["session"], "2");
+                assert_eq!(capture.with_payload("type", "StartAI").len(), 2);
+                assert_eq!(planet.final_report().sunrays, 2, "Lifetime counters are never reset");
+            }
+        }
+    }
+
+    // Energy-aware service tiers.
+    mod service_tiers {
+        use super::*;
+
+        #[test]
+        fn test_service_tiers() {
+            // SCENARIO: As the cells run out, the planet serves every explorer, then only
+            // priority explorers, then none of them.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_log_sink(capture.clone())
+                .with_service_tiers(ServiceTiers::new([7]));
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..3 {
+                planet.sunray();
+            }
+
+            // 3 of 5 cells charged: full service
+            assert_eq!(available_cells(&mut planet, 9), 3);
+            assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+
+            // 2 of 5: priority explorers only
+            assert_eq!(available_cells(&mut planet, 9), 0);
+            assert_eq!(available_cells(&mut planet, 7), 2);
+            assert!(!generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            assert!(generate(&mut planet, 7, BasicResourceType::Hydrogen));
+
+            // 1 of 5: the last cell is kept for a rocket
+            assert_eq!(available_cells(&mut planet, 7), 0);
+            assert!(!generate(&mut planet, 7, BasicResourceType::Hydrogen));
+            assert_eq!(planet.state.charged_count(), 1);
+
+            let tiers: Vec<_> = capture
+                .with_payload_key("serviceTier")
+                .iter()
+                .map(|e| e.payload["serviceTier"].clone())
+                .collect();
+            assert_eq!(
+                tiers,
+                ["Full", "Full", "PriorityOnly", "PriorityOnly", "PriorityOnly", "PriorityOnly", "RocketOnly", "RocketOnly"]
+            );
+        }
+
+        #[test]
+        fn test_service_tiers_honour_reservations() {
+            // SCENARIO: Cells reserved while energy was abundant are still handed out
+            // once the tier would refuse the explorer.
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_reservation(ReservationPolicy { max_cells: 1, ttl: 100 })
+                .with_service_tiers(ServiceTiers::new([]));
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..3 {
+                planet.sunray();
+            }
+            assert_eq!(available_cells(&mut planet, 9), 1);
+            assert!(generate(&mut planet, 7, BasicResourceType::Hydrogen));
+
+            // Now PriorityOnly, and 7 is not a priority explorer
+            assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            assert!(!generate(&mut planet, 7, BasicResourceType::Hydrogen));
+        }
+    }
+
+    // Parsing and serializing the rocket strategies.
+    mod strategy_names {
+        use super::*;
+
+        #[test]
+        fn test_rocket_strategy_from_str() {
+            for strategy in RocketStrategy::iter() {
+                assert_eq!(strategy.to_string().parse::<RocketStrategy>(), Ok(strategy.clone()));
+                assert_eq!(strategy.to_string().to_lowercase().parse::<RocketStrategy>(), Ok(strategy));
+            }
+            assert_eq!("EMERGENCYRESERVE".parse(), Ok(RocketStrategy::EmergencyReserve));
+            let err = "reckless".parse::<RocketStrategy>().unwrap_err();
+            assert!(err.contains("Disabled, Default, Safe, EmergencyReserve, Stockpile, Planner"), "{err}");
+            assert_eq!(RocketStrategy::iter().count(), 6);
+        }
+
+        #[cfg(feature = "serde")]
+        #[test]
+        fn test_serde_round_trip() {
+            for strategy in RocketStrategy::iter() {
+                let json = serde_json::to_string(&strategy).unwrap();
+                assert_eq!(json, format!("\"{strategy}\""));
+                assert_eq!(serde_json::from_str::<RocketStrategy>(&json).unwrap(), strategy);
+                assert_eq!(serde_json::from_str::<RocketStrategy>(&json.to_lowercase()).unwrap(), strategy);
+            }
+
+            let tiers: ServiceTiers = serde_json::from_str(r#"{ "priority_explorers": [7] }"#).unwrap();
+            assert_eq!(tiers, ServiceTiers::new([7]));
+            let policy = ReservationPolicy { max_cells: 2, ttl: 5 };
+            let json = serde_json::to_string(&policy).unwrap();
+            assert_eq!(serde_json::from_str::<ReservationPolicy>(&json).unwrap(), policy);
+            assert_eq!(
+                serde_json::from_str::<SessionCounters>("\"ResetOnStart\"").unwrap(),
+                SessionCounters::ResetOnStart
+            );
+            let selection = CellSelection::LowestForRockets { cells: 2 };
+            let json = serde_json::to_string(&selection).unwrap();
+            assert_eq!(serde_json::from_str::<CellSelection>(&json).unwrap(), selection);
+            let admission = AdmissionPolicy { blacklist: HashSet::from([13]), ..Default::default() };
+            let json = serde_json::to_string(&admission).unwrap();
+            assert_eq!(serde_json::from_str::<AdmissionPolicy>(&json).unwrap(), admission);
+        }
+    }
+
+    // The Stockpile strategy.
+    mod stockpile {
+        use super::*;
+
+        #[test]
+        fn test_stockpile_survives_asteroid_bursts() {
+            // SCENARIO: With a cap of 3, the planet keeps one rocket and two cells for the
+            // next ones, sells only the rest, and then deflects three asteroids in a row.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Stockpile, Some(BasicResourceType::Hydrogen))
+                .with_stockpile_cap(3)
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..5 {
+                planet.sunray();
+            }
+            assert!(planet.internal_state().has_rocket);
+            assert_eq!(planet.state.charged_count(), 4);
+
+            assert_eq!(available_cells(&mut planet, 9), 2);
+            assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            assert!(!generate(&mut planet, 9, BasicResourceType::Hydrogen), "Kept cells were sold");
+
+            for _ in 0..3 {
+                assert!(planet.asteroid().is_some(), "The stockpile ran out too early");
+            }
+            assert!(planet.asteroid().is_none());
+
+            let stockpiles: Vec<_> = capture
+                .with_payload("type", "AsteroidAck")
+                .iter()
+                .map(|e| e.payload["rocketStockpile"].clone())
+                .collect();
+            assert_eq!(stockpiles, ["2", "1", "0", "0"]);
+        }
+
+        #[test]
+        fn test_stockpile_keeps_nothing_without_rockets() {
+            // SCENARIO: A planet type that can't have rockets sells every cell.
+            let config = PlanetConfig::new(1, RocketStrategy::Stockpile, Some(BasicResourceType::Hydrogen))
+                .with_planet_type(PlanetType::D);
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..3 {
+                planet.sunray();
+            }
+            assert_eq!(available_cells(&mut planet, 9), 3);
+            assert!(planet.asteroid().is_none());
+        }
+
+        #[test]
+        #[should_panic(expected = "the stockpile cap must be at least 1")]
+        fn test_stockpile_cap_keeps_at_least_the_rocket() {
+            let _ = PlanetConfig::new(1, RocketStrategy::Stockpile, None).with_stockpile_cap(0);
+        }
+    }
+
+    // The Planner strategy.
+    mod planner_strategy {
+        use super::*;
+
+        #[test]
+        fn test_planner_logs_every_decision() {
+            // SCENARIO: The planner builds while asteroids look likely, waits once explorers
+            // dominate the traffic, and still builds on demand when an asteroid hits.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Planner, Some(BasicResourceType::Hydrogen))
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+
+            planet.sunray();
+            assert!(planet.internal_state().has_rocket);
+            for _ in 0..20 {
+                available_cells(&mut planet, 9);
+            }
+            planet.sunray();
+            assert!(planet.asteroid().is_some());
+            assert!(!planet.internal_state().has_rocket, "The planner should have waited");
+            assert!(planet.asteroid().is_some(), "The planner should build on demand");
+
+            let decisions = capture.with_payload("type", "PlannerDecision");
+            let summary: Vec<_> = decisions
+                .iter()
+                .map(|e| (e.payload["handler"].as_str(), e.payload["choice"].as_str()))
+                .collect();
+            assert_eq!(
+                summary,
+                [("Sunray", "BuildRocket"), ("Asteroid", "Wait"), ("Asteroid", "BuildRocket")]
+            );
+            assert!(decisions[1].payload.contains_key("keepEnergy"));
+            assert!(!decisions[2].payload.contains_key("wait"), "No waiting while an asteroid hits");
+        }
+    }
 
-                let mut p = Payload::new();
-                p.insert("type".to_string(), "AvailableEnergyCellResponse".to_string());
-                p.insert(
-                    "internalEnergyCellCount".to_string(),
-                    format!("{:?}", count),
-                );
-                p.insert(
-                    "rocketStrategy".to_string(),
-                    self.rocket_strategy.to_string(),
-                );
+    // Sunray forecast and energy budget.
+    mod energy_budget {
+        use super::*;
+
+        #[test]
+        fn test_energy_budget() {
+            // SCENARIO: Sunrays are rare, so the period budget sells two of the three
+            // charged cells and keeps the last one to build a rocket.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Default, Some(BasicResourceType::Hydrogen))
+                .with_energy_budget(EnergyBudget { period: 10 })
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..3 {
+                planet.sunray();
+            }
+            for _ in 0..20 {
+                planet.internal_state();
+            }
 
-                let available_cells = match self.rocket_strategy {
-                    RocketStrategy::EmergencyReserve => count.saturating_sub(1) as u32,
-                    _ => count as u32,
-                };
+            let forecast = planet.ai.forecast(&planet.state, 10);
+            assert!(forecast.sunrays < 2.0, "{forecast:?}");
+            assert_eq!(forecast.charged_cells, 3.0 + forecast.sunrays);
 
-                p.insert("sentEnergyCellCount".to_string(), format!("{:?}", count));
+            assert_eq!(available_cells(&mut planet, 9), 2);
+            assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            assert!(!generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            assert_eq!(planet.state.charged_count(), 1);
 
-                p.insert("Result".to_string(), "Failure".to_string());
-                let log = LogEvent::new(
-                    Some(Participant::new(ActorType::Planet, state.id())),
-                    Some(Participant::new(ActorType::Explorer, explorer_id)),
-                    EventType::MessagePlanetToExplorer,
-                    Channel::Trace,
-                    p,
-                );
-                log.emit();
+            let budgets = capture.with_payload("type", "EnergyBudget");
+            assert_eq!(budgets.len(), 1);
+            assert_eq!(budgets[0].payload["budgetCells"], "2");
+            assert_eq!(capture.with_payload("budgetExhausted", "true").len(), 1);
+        }
 
-                Some(PlanetToExplorer::AvailableEnergyCellResponse { available_cells })
+        #[test]
+        fn test_energy_budget_without_rockets() {
+            // SCENARIO: Planets that never build rockets keep nothing for the asteroids
+            // and can sell all their charged cells.
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_energy_budget(EnergyBudget { period: 10 });
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..3 {
+                planet.sunray();
             }
+            for _ in 0..40 {
+                planet.internal_state();
+            }
+            let forecast = planet.ai.forecast(&planet.state, 10);
+            assert!(forecast.sunrays < 1.0 && forecast.asteroids > 0.0, "{forecast:?}");
+            assert_eq!(available_cells(&mut planet, 9), 3);
+
+            let config = PlanetConfig::new(2, RocketStrategy::Default, Some(BasicResourceType::Hydrogen))
+                .with_planet_type(PlanetType::B)
+                .with_energy_budget(EnergyBudget { period: 10 });
+            let mut planet = simulated_configured_planet(config);
+            planet.sunray();
+            for _ in 0..20 {
+                planet.internal_state();
+            }
+            assert!(planet.ai.forecast(&planet.state, 10).asteroids > 0.0);
+            assert_eq!(available_cells(&mut planet, 9), 1, "Type B planets have no rockets");
+            assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
         }
     }
-}
-
-/// Tries to build a rocket using the first fully charged energy cell.
-/// Returns `Some(index)` on success, or `None` on failure.
-///
-/// This helper extracts a full cell through `state.full_cell()`, which provides
-/// both the mutable reference and its index. If no full cell exists or the
-/// rocket cannot be built, the function returns `None`.
-fn try_build_rocket(state: &mut PlanetState) -> Option<usize> {
-    let Some((_, cell_index)) = state.full_cell() else {
-        return None;
-    };
-    state.build_rocket(cell_index).ok()?; // if Err -> return None
 
-    Some(cell_index)
-}
+    // Cell selection.
+    mod cell_selection_policy {
+        use super::*;
+
+        #[test]
+        fn test_cells_dedicated_to_rockets() {
+            // SCENARIO: Cell 0 is kept for defence: explorers are served from the other
+            // cells, and the rocket built when the asteroid comes uses cell 0.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Default, Some(BasicResourceType::Hydrogen))
+                .with_cell_selection(CellSelection::LowestForRockets { cells: 1 })
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+            planet.sunray();
+            planet.sunray();
+
+            assert_eq!(available_cells(&mut planet, 9), 1);
+            assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            assert!(!generate(&mut planet, 9, BasicResourceType::Hydrogen), "Sold the rocket cell");
+            assert!(planet.asteroid().is_some());
+
+            let sold = capture.with_payload("Result", "Success");
+            assert_eq!(sold[0].payload["cellIndex"], "1");
+            let ack = capture.with_payload("type", "AsteroidAck");
+            assert_eq!(ack[0].payload["rocketCells"], "[0]");
+        }
 
-/// Creates and initializes a new `Planet` instance with a predefined set of
-/// generation and combination rules, a basic AI model, and the communication
-/// channels used to interact with the orchestrator and explorers.
-///
-/// Planet configuration
-/// - Type: C
-/// - Generation rule: Oxygen
-/// - Combination rules: Diamond, Water, Life, Robot, Dolphin, AIPartner
-///
-/// Parameters
-/// - The channels used to receive messages from the orchestrator and
-///   send responses back
-/// - The channel used to receive messages from explorers
-/// - planet_id: the id of the planet
-/// - rocket_strategy: takes an Option<BasicResourceType> where BasicResourceType is an Enum containing:
-///     - Disabled: do not generate rockets under any condition.
-///     - Default: generate a rocket only when an asteroid is coming.
-///     - Safe: always rebuild a rocket when there isn't any
-///     - EmergencyReserve: same as `Safe`, but preserves one fully charged cell for emergencies.
-/// - basic_resource: takes an Option<BasicResourceType> and set that one as a basic resource for the planet
-///
-/// Returns:
-/// - `Ok(Planet)` if the configuration is valid for the selected planet type
-/// - `Err(String)` if the rules exceed the constraints of the planet type
-pub fn houston_we_have_a_borrow(
-    rx_orchestrator: Receiver<OrchestratorToPlanet>,
-    tx_orchestrator: Sender<PlanetToOrchestrator>,
-    rx_explorer: Receiver<ExplorerToPlanet>,
-    planet_id: u32,
-    rocket_strategy: RocketStrategy,
-    basic_resource: Option<BasicResourceType>,
-) -> Result<Planet, String> {
-    let gen_rules = if let Some(b_res) = basic_resource {
-        vec![b_res]
-    } else {
-        vec![
-            // BasicResourceType::Oxygen,
-            BasicResourceType::Hydrogen,
-            // BasicResourceType::Carbon,
-            // BasicResourceType::Silicon,
-        ]
-    };
+        #[test]
+        fn test_round_robin_spreads_the_wear() {
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_cell_selection(CellSelection::RoundRobin)
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..3 {
+                planet.sunray();
+                planet.sunray();
+                assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            }
+            let cells: Vec<_> = capture
+                .with_payload("Result", "Success")
+                .iter()
+                .map(|e| e.payload["cellIndex"].clone())
+                .collect();
+            assert_eq!(cells, ["0", "1", "2"]);
+        }
 
-    let comb_rules = vec![
-        // ComplexResourceType::Diamond,
-        // ComplexResourceType::Water,
-        // ComplexResourceType::Life,
-        // ComplexResourceType::Robot,
-        // ComplexResourceType::Dolphin,
-        // ComplexResourceType::AIPartner,
-    ];
-    let ai = PlanetCoreThinkingModel {
-        rocket_strategy : rocket_strategy.clone(),
-        basic_resource: basic_resource.unwrap_or(BasicResourceType::Hydrogen),
-    };
+        #[test]
+        fn test_unsupported_request_keeps_the_round_robin_turn() {
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_cell_selection(CellSelection::RoundRobin)
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..3 {
+                planet.sunray();
+                planet.sunray();
+                assert!(!generate(&mut planet, 9, BasicResourceType::Oxygen));
+                assert!(generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            }
+            let cells: Vec<_> = capture
+                .with_payload("Result", "Success")
+                .iter()
+                .map(|e| e.payload["cellIndex"].clone())
+                .collect();
+            assert_eq!(cells, ["0", "1", "2"]);
+        }
+    }
 
+    // Batch generation.
+    mod batches {
+        use super::*;
+
+        #[test]
+        fn test_generate_batch() {
+            // SCENARIO: Two explorers ask for more than they can get in one batch each;
+            // the batches stop at the reserved cells and at the empty planet.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_reservation(ReservationPolicy { max_cells: 2, ttl: 100 })
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+            for _ in 0..4 {
+                planet.sunray();
+            }
+            assert_eq!(available_cells(&mut planet, 7), 2);
+
+            let batch = planet.generate_batch(9, BasicResourceType::Hydrogen, 5);
+            assert_eq!((batch.resources.len(), batch.stop), (2, BatchStop::NoFreeCell));
+            assert!(batch.resources.iter().all(|r| matches!(r, BasicResource::Hydrogen(_))));
+            let batch = planet.generate_batch(7, BasicResourceType::Hydrogen, 2);
+            assert_eq!((batch.resources.len(), batch.stop), (2, BatchStop::Completed));
+            assert_eq!(planet.state.charged_count(), 0);
+
+            planet.sunray();
+            let batch = planet.generate_batch(7, BasicResourceType::Oxygen, 1);
+            assert_eq!((batch.resources.len(), batch.stop), (0, BatchStop::Unsupported));
+            assert_eq!(planet.state.charged_count(), 1);
+
+            let report = planet.final_report();
+            assert_eq!((report.explorer_requests, report.resources_generated), (4, 4));
+            let stops: Vec<_> = capture
+                .with_payload("type", "GenerateBatchResponse")
+                .iter()
+                .map(|e| format!("{}/{} {}", e.payload["generated"], e.payload["requested"], e.payload["stopReason"]))
+                .collect();
+            assert_eq!(stops, ["2/5 NoFreeCell", "2/2 Completed", "0/1 Unsupported"]);
+        }
+    }
 
-    let mut p = Payload::new();
-    p.insert("type".to_string(), "Creation".to_string());
-    p.insert("planetId".to_string(), planet_id.to_string());
-    p.insert("basicResourceRule".to_string(), format!("{:?}", basic_resource.unwrap_or(BasicResourceType::Hydrogen)));
-    p.insert("planetType".to_string(), format!("{:?}",PlanetType::A));
-    p.insert("rocketStrategy".to_string(), format!("{:?}",rocket_strategy));
-    LogEvent::new(
-        Some(Participant::new(ActorType::Planet, planet_id)),
-        Some(Participant::new(ActorType::SelfActor, planet_id)),
-        // ActorType::Planet,
-        // planet_id,
-        // ActorType::SelfActor,
-        // 0u32.to_string(),
-        EventType::InternalPlanetAction,
-        Channel::Info,
-        p,
-    ).emit();
+    // Explorer sessions.
+    mod explorer_sessions {
+        use super::*;
+
+        #[test]
+        fn test_explorer_sessions() {
+            // SCENARIO: An explorer lands, trades and leaves; its session is summarized.
+            // Another explorer never landed and has no session.
+            let capture = CaptureSink::for_planet(1);
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+            planet.sunray();
+            planet.explorer_arrival(7);
+            planet.sunray();
+
+            assert_eq!(available_cells(&mut planet, 7), 2);
+            assert_eq!(planet.generate_batch(7, BasicResourceType::Hydrogen, 3).resources.len(), 2);
+            assert!(!generate(&mut planet, 7, BasicResourceType::Oxygen));
+            assert!(!generate(&mut planet, 9, BasicResourceType::Hydrogen));
+            let session = planet.ai.explorer_session(7).unwrap();
+            assert_eq!((session.arrived_at, session.requests, session.resources_received), (1, 3, 2));
+            assert!(planet.ai.explorer_session(9).is_none());
+
+            planet.explorer_departure(7);
+            planet.explorer_departure(9);
+            assert!(planet.ai.explorer_session(7).is_none());
+            let summaries = capture.with_payload("type", "ExplorerSessionSummary");
+            assert_eq!(summaries.len(), 1);
+            let summary = &summaries[0].payload;
+            assert_eq!(summary["explorerId"], "7");
+            assert_eq!(summary["departedAt"], "6");
+            assert_eq!((summary["requests"].as_str(), summary["cellsConsumed"].as_str()), ("3", "2"));
+        }
+    }
 
-    Planet::new(
-        planet_id,
-        PlanetType::A,
-        Box::new(ai),
-        gen_rules,
-        comb_rules,
-        (rx_orchestrator, tx_orchestrator),
-        rx_explorer,
-    )
+    // Explorer admission.
+    mod explorer_policy {
+        use super::*;
+
+        #[test]
+        fn test_explorer_policy() {
+            // SCENARIO: A planet serving at most one explorer rejects a second one and a
+            // blacklisted one, tells the orchestrator why, and admits them once there is room.
+            let capture = CaptureSink::for_planet(1);
+            let policy = AdmissionPolicy {
+                max_explorers: Some(1),
+                blacklist: HashSet::from([13]),
+                min_charged_cells: 0,
+            };
+            let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+                .with_explorer_policy(policy)
+                .with_log_sink(capture.clone());
+            let mut planet = simulated_configured_planet(config);
+            planet.sunray();
+            planet.explorer_arrival(7);
+            planet.explorer_arrival(8);
+            planet.explorer_arrival(13);
+
+            assert_eq!(available_cells(&mut planet, 7), 1);
+            assert!(planet.explorer_msg(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 8 }).is_none());
+            assert!(planet.explorer_msg(ExplorerToPlanet::SupportedCombinationRequest { explorer_id: 8 }).is_none());
+            assert_eq!(planet.generate_batch(13, BasicResourceType::Hydrogen, 1).stop, BatchStop::Rejected);
+            assert_eq!(planet.state.charged_count(), 1);
+
+            planet.explorer_departure(7);
+            planet.explorer_departure(8);
+            planet.explorer_arrival(8);
+            assert!(generate(&mut planet, 8, BasicResourceType::Hydrogen));
+
+            let decisions: Vec<_> = capture
+                .with_payload("type", "ExplorerAdmission")
+                .iter()
+                .map(|e| {
+                    let reason = e.payload.get("reason").map_or(String::new(), |r| format!(": {r}"));
+                    format!("{} {}{reason}", e.payload["explorerId"], e.payload["decision"])
+                })
+                .collect();
+            assert_eq!(
+                decisions,
+                ["7 Accepted", "8 Rejected: 1 explorers already on the planet", "13 Rejected: blacklisted", "8 Accepted"]
+            );
+            assert_eq!(capture.with_payload("type", "ExplorerRejected").len(), 2);
+            // Only the accepted visit of 7 was a session
+            assert_eq!(capture.with_payload("type", "ExplorerSessionSummary").len(), 1);
+        }
+    }
 }
+
 #[cfg(test)]
-mod tests {
+mod end_to_end {
     use super::*;
     use common_game::components::forge::Forge;
-    use common_game::components::resource::BasicResourceType;
-    use common_game::protocols::planet_explorer::*;
-    use common_game::protocols::orchestrator_planet::*;
     use crossbeam_channel::{unbounded, Receiver, Sender};
     use std::sync::OnceLock;
     use std::thread;
@@ -897,24 +2431,31 @@ mod tests {
         Receiver<PlanetToOrchestrator>,
         Sender<ExplorerToPlanet>,
         Receiver<PlanetToExplorer>,
+    ) {
+        spawn_configured_planet(PlanetConfig::new(1, strategy, Some(resource)))
+    }
+
+    // Same as `spawn_test_planet`, for tests that need non-default options.
+    fn spawn_configured_planet(
+        config: PlanetConfig,
+    ) -> (
+        Sender<OrchestratorToPlanet>,
+        Receiver<PlanetToOrchestrator>,
+        Sender<ExplorerToPlanet>,
+        Receiver<PlanetToExplorer>,
     ) {
         // 1. Create Channels
         let (orch_tx, orch_rx) = unbounded();          // Test -> Planet (Orch)
         let (planet_to_orch_tx, planet_to_orch_rx) = unbounded(); // Planet -> Test (Orch)
 
         let (expl_tx, expl_rx) = unbounded();          // Test -> Planet (Expl)
-        // We need a channel to receive Explorer responses.
-        // We will inject this via the Handshake message.
-        let (test_expl_response_tx, test_expl_response_rx) = unbounded();
 
-        // 2. Instantiate Planet using your helper function `new_planet`
-        let mut planet = houston_we_have_a_borrow(
+        // 2. Instantiate Planet from the given configuration
+        let mut planet = create_planet(
+            config,
             orch_rx,
             planet_to_orch_tx,
             expl_rx,
-            1, // Planet ID
-            strategy,
-            Some(resource),
         ).expect("Failed to create planet instance");
 
         // 3. Run Planet in Background Thread
@@ -929,272 +2470,84 @@ mod tests {
         let _ = planet_to_orch_rx.recv().unwrap();
 
         // 5. Register our Test Explorer (Handshake)
-        orch_tx.send(OrchestratorToPlanet::IncomingExplorerRequest {
-            explorer_id: 99,
-            new_sender: test_expl_response_tx
-        }).unwrap();
-        // Wait for Handshake Ack
-        let _ = planet_to_orch_rx.recv().unwrap();
+        let test_expl_response_rx = register_explorer(&orch_tx, &planet_to_orch_rx, 99);
 
         (orch_tx, planet_to_orch_rx, expl_tx, test_expl_response_rx)
     }
 
-    // ==========================================
-    // TESTS
-    // ==========================================
-
-    #[test]
-    fn test_strategy_safe_builds_rocket_immediately() {
-        // SCENARIO: Safe strategy should build a rocket immediately after receiving energy.
-        let forge = get_forge();
-        let (orch_tx, orch_rx, _, _) = spawn_test_planet(RocketStrategy::Safe, BasicResourceType::Hydrogen);
-
-        // 1. Send Sunray
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-
-        // 2. Wait for SunrayAck
-        let ack = orch_rx.recv_timeout(Duration::from_secs(1)).expect("Timeout waiting for SunrayAck");
-
-        // 3. Verify Internal State
-        // Since we can't inspect PlanetState directly, we ask the planet for its state.
-        orch_tx.send(OrchestratorToPlanet::InternalStateRequest).unwrap();
-
-        let state_msg = orch_rx.recv_timeout(Duration::from_secs(1)).expect("Timeout waiting for State");
-
-        if let PlanetToOrchestrator::InternalStateResponse { planet_state, .. } = state_msg {
-            // The 'Safe' strategy logic is: If I have energy, make a rocket.
-            assert!(planet_state.has_rocket, "Safe strategy failed to build rocket immediately");
-        } else {
-            panic!("Unexpected response type: no debug trait "); //{:?}",  state_msg);
-        }
-    }
-
-    #[test]
-    fn test_strategy_default_waits_for_asteroid() {
-        // SCENARIO: Default strategy keeps energy stored and only builds when threatened.
-        let forge = get_forge();
-        let (orch_tx, orch_rx, _, _) = spawn_test_planet(RocketStrategy::Default, BasicResourceType::Hydrogen);
-
-        // 1. Send Sunray
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv(); // Consume Ack
-
-        // 2. Verify NO Rocket yet
-        orch_tx.send(OrchestratorToPlanet::InternalStateRequest).unwrap();
-        let state_msg = orch_rx.recv().unwrap();
-
-        if let PlanetToOrchestrator::InternalStateResponse { planet_state, .. } = state_msg {
-            assert!(!planet_state.has_rocket, "Default strategy built rocket too early!");
-            assert!(planet_state.charged_cells_count > 0, "Default strategy lost the energy!");
-        }
-
-        // 3. Send Asteroid
-        orch_tx.send(OrchestratorToPlanet::Asteroid(forge.generate_asteroid())).unwrap();
-
-        // 4. Expect Rocket Launch (AsteroidAck with Rocket)
-        let ack = orch_rx.recv_timeout(Duration::from_secs(1)).expect("Timeout waiting for AsteroidAck");
-
-        if let PlanetToOrchestrator::AsteroidAck { rocket, .. } = ack {
-            assert!(rocket.is_some(), "Default strategy failed to build rocket for asteroid");
-        } else {
-            panic!("Wrong message type received for Asteroid");
-        }
-    }
-
-    #[test]
-    fn test_emergency_reserve_deception() {
-        // SCENARIO: EmergencyReserve keeps 1 cell hidden.
-        // If we only give it 1 Sunray, it should claim to be empty.
-        let forge = get_forge();
-        let (orch_tx, orch_rx, expl_tx, expl_rx) = spawn_test_planet(RocketStrategy::EmergencyReserve, BasicResourceType::Hydrogen);
-
-        // 1. Charge exactly 1 cell
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv(); // Consume Ack
-
-        // 2. Check Orchestrator Report (The "Lie")
-        orch_tx.send(OrchestratorToPlanet::InternalStateRequest).unwrap();
-        let state_msg = orch_rx.recv().unwrap();
-
-        if let PlanetToOrchestrator::InternalStateResponse { planet_state, .. } = state_msg {
-            // Real state is 1, but logic subtracts 1.
-            assert_eq!(planet_state.charged_cells_count, 0, "EmergencyReserve failed to hide the reserve cell from Orchestrator");
-        }
-
-        // 3. Check Explorer Availability (The "Denial")
-        expl_tx.send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 99 }).unwrap();
-        let expl_resp = expl_rx.recv().unwrap();
-
-        if let PlanetToExplorer::AvailableEnergyCellResponse { available_cells } = expl_resp {
-            assert_eq!(available_cells, 0, "EmergencyReserve failed to hide reserve cell from Explorer");
-        }
-
-        // 4. Verify Resource Generation fails
-        expl_tx.send(ExplorerToPlanet::GenerateResourceRequest {
-            explorer_id: 99,
-            resource: BasicResourceType::Hydrogen
+    // Lands an explorer on the planet and returns the channel its responses arrive on.
+    fn register_explorer(
+        orch_tx: &Sender<OrchestratorToPlanet>,
+        orch_rx: &Receiver<PlanetToOrchestrator>,
+        explorer_id: u32,
+    ) -> Receiver<PlanetToExplorer> {
+        // We need a channel to receive Explorer responses.
+        // We will inject this via the Handshake message.
+        let (response_tx, response_rx) = unbounded();
+        orch_tx.send(OrchestratorToPlanet::IncomingExplorerRequest {
+            explorer_id,
+            new_sender: response_tx
         }).unwrap();
-
-        // We expect no success response because the logic returns `None` when hitting the reserve.
-        let result = expl_rx.recv_timeout(Duration::from_millis(200));
-        assert!(result.is_err(), "Planet should not generate resources using the emergency reserve");
+        // Wait for Handshake Ack
+        let _ = orch_rx.recv().unwrap();
+        response_rx
     }
 
     #[test]
-    fn test_resource_generation_match() {
-        // SCENARIO: Planet is set to produce Oxygen. Request Oxygen (Success) then Carbon (Failure).
+    fn test_messages_reach_the_ai() {
+        // SCENARIO: Orchestrator and explorer messages go through the runtime to the AI and back.
         let forge = get_forge();
         let (orch_tx, orch_rx, expl_tx, expl_rx) = spawn_test_planet(RocketStrategy::Default, BasicResourceType::Oxygen);
 
         // 1. Charge Up
         orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv();
+        let ack = orch_rx.recv_timeout(Duration::from_secs(1)).expect("Timeout waiting for SunrayAck");
+        assert!(matches!(ack, PlanetToOrchestrator::SunrayAck { .. }));
 
         // 2. Request Correct Resource (Oxygen)
         expl_tx.send(ExplorerToPlanet::GenerateResourceRequest {
             explorer_id: 99,
             resource: BasicResourceType::Oxygen
         }).unwrap();
-
         let resp = expl_rx.recv_timeout(Duration::from_secs(1)).expect("Should generate Oxygen");
-        match resp {
-            PlanetToExplorer::GenerateResourceResponse { resource: Some(_) } => assert!(true),
-            _ => panic!("Failed to generate correct resource"),
-        }
-
-        // 3. Recharge (assume previous request used the energy)
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv();
+        assert!(
+            matches!(resp, PlanetToExplorer::GenerateResourceResponse { resource: Some(_) }),
+            "Failed to generate correct resource"
+        );
 
-        // 4. Request Incorrect Resource (Carbon)
+        // 3. Refused requests get no reply -> Timeout on channel
         expl_tx.send(ExplorerToPlanet::GenerateResourceRequest {
             explorer_id: 99,
             resource: BasicResourceType::Carbon
         }).unwrap();
-
-        // Should return None (impl logic) -> Timeout on channel
         let result = expl_rx.recv_timeout(Duration::from_millis(200));
         assert!(result.is_err(), "Planet generated a resource it does not support!");
-    }
-    #[test]
-    fn test_safe_strategy_rapid_reload() {
-        // SCENARIO: 'Safe' strategy has a rocket AND extra energy.
-        // When it fires the rocket at an asteroid, it should immediately build a NEW one using the spare energy.
-
-        let forge = get_forge();
-        let (orch_tx, orch_rx, _, _) = spawn_test_planet(RocketStrategy::Safe, BasicResourceType::Hydrogen);
-
-        // 1. Charge TWICE (1st -> builds rocket, 2nd -> stored as spare energy)
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv(); // Ack 1
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv(); // Ack 2
-
-        // 2. Verify State before attack: Has Rocket + 1 Spare Cell (Total 2 cells used)
-        orch_tx.send(OrchestratorToPlanet::InternalStateRequest).unwrap();
-        if let Ok(PlanetToOrchestrator::InternalStateResponse { planet_state, .. }) = orch_rx.recv() {
-            assert!(planet_state.has_rocket, "Should have initial rocket");
-            // Depending on implementation, a rocket might consume the cell or sit on top of it.
-            // Usually: 1 cell became a rocket, 1 cell is charged.
-        }
-
-        // 3. Send Asteroid (Forces launch)
-        orch_tx.send(OrchestratorToPlanet::Asteroid(forge.generate_asteroid())).unwrap();
-
-        // 4. Receive Ack with Rocket
-        let ack = orch_rx.recv_timeout(Duration::from_secs(1)).expect("Timeout waiting for AsteroidAck");
-        if let PlanetToOrchestrator::AsteroidAck { rocket, .. } = ack {
-            assert!(rocket.is_some(), "Planet should have fired");
-        }
-
-        // 5. CRITICAL CHECK: Did it reload?
-        // The 'Safe' logic says: after taking rocket, if strategy is Safe, try_build_rocket(state).
-        orch_tx.send(OrchestratorToPlanet::InternalStateRequest).unwrap();
-        let state_msg = orch_rx.recv().unwrap();
-
-        if let PlanetToOrchestrator::InternalStateResponse { planet_state, .. } = state_msg {
-            assert!(planet_state.has_rocket, "Safe strategy failed to auto-reload rocket using spare energy!");
-        }
-    }
-
-    #[test]
-    fn test_strategy_disabled_is_defenseless() {
-        // SCENARIO: 'Disabled' strategy should accumulate energy but NEVER build a rocket,
-        // even if an asteroid is about to kill it.
-
-        let forge = get_forge();
-        let (orch_tx, orch_rx, _, _) = spawn_test_planet(RocketStrategy::Disabled, BasicResourceType::Hydrogen);
-
-        // 1. Charge up (Disabled strategy should just store this as raw energy)
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv();
-
-        // 2. Verify Internal State (Energy: Yes, Rocket: No)
-        orch_tx.send(OrchestratorToPlanet::InternalStateRequest).unwrap();
-        if let Ok(PlanetToOrchestrator::InternalStateResponse { planet_state, .. }) = orch_rx.recv() {
-            assert!(!planet_state.has_rocket, "Disabled strategy should not build rocket");
-            assert!(planet_state.charged_cells_count > 0, "Disabled strategy should store energy");
-        }
 
-        // 3. Send Asteroid
+        // 4. Asteroid with no energy left: the ack carries no rocket
         orch_tx.send(OrchestratorToPlanet::Asteroid(forge.generate_asteroid())).unwrap();
-
-        // 4. Expect Ack with NO Rocket (Planet Doom)
         let ack = orch_rx.recv_timeout(Duration::from_secs(1)).expect("Timeout waiting for AsteroidAck");
-
-        if let PlanetToOrchestrator::AsteroidAck { rocket, .. } = ack {
-            assert!(rocket.is_none(), "Disabled strategy should NOT fire a rocket, even in emergency");
-        }
+        assert!(matches!(ack, PlanetToOrchestrator::AsteroidAck { rocket: None, .. }));
     }
 
     #[test]
-    fn test_emergency_reserve_allows_surplus_use() {
-        // SCENARIO: Planet has received 3 Cells. Reserve is 1. 1 becomes a rocket
-        // It should allow generating 1 resource (dropping to 1 cell), then REFUSE the next request.
-
+    fn test_responses_are_routed_per_explorer() {
+        // SCENARIO: With reservations, two explorers get different answers on their own channels.
         let forge = get_forge();
-        let (orch_tx, orch_rx, expl_tx, expl_rx) = spawn_test_planet(RocketStrategy::EmergencyReserve, BasicResourceType::Hydrogen);
+        let config = PlanetConfig::new(1, RocketStrategy::Disabled, Some(BasicResourceType::Hydrogen))
+            .with_reservation(ReservationPolicy::default());
+        let (orch_tx, orch_rx, expl_tx, expl_rx) = spawn_configured_planet(config);
+        let other_rx = register_explorer(&orch_tx, &orch_rx, 98);
 
-        // 1. Charge TRICE (Total 3 cells)
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv();
-        orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
-        let _ = orch_rx.recv();
         orch_tx.send(OrchestratorToPlanet::Sunray(forge.generate_sunray())).unwrap();
         let _ = orch_rx.recv();
 
-        // 2. Explorer requests Availability
         expl_tx.send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 99 }).unwrap();
-        let avail_resp = expl_rx.recv().unwrap();
-        if let PlanetToExplorer::AvailableEnergyCellResponse { available_cells } = avail_resp {
-            // Actual 2, Reserve 1 -> Reports 1
-            assert_eq!(available_cells, 1, "Should report surplus cells only (Actual 2 - Reserve 1 = 1)");
-        }
-
-        // 3. Generate Resource (Should SUCCEED using the surplus cell)
-        expl_tx.send(ExplorerToPlanet::GenerateResourceRequest {
-            explorer_id: 99,
-            resource: BasicResourceType::Hydrogen
-        }).unwrap();
-
-        let gen_resp = expl_rx.recv_timeout(Duration::from_secs(1)).expect("Should succeed with surplus");
-        assert!(matches!(gen_resp, PlanetToExplorer::GenerateResourceResponse { resource: Some(_) }));
-
-        // 4. Verify we are now down to the Reserve
-        orch_tx.send(OrchestratorToPlanet::InternalStateRequest).unwrap();
-        if let Ok(PlanetToOrchestrator::InternalStateResponse { planet_state, .. }) = orch_rx.recv() {
-            // The AI "lies" and says 0, because it subtracts the reserve
-            assert_eq!(planet_state.charged_cells_count, 0, "Should now report 0 (masking the last reserve cell)");
-        }
-
-        // 5. Try to Generate AGAIN (Should FAIL/Timeout)
-        expl_tx.send(ExplorerToPlanet::GenerateResourceRequest {
-            explorer_id: 99,
-            resource: BasicResourceType::Hydrogen
-        }).unwrap();
+        let resp = expl_rx.recv_timeout(Duration::from_secs(1)).expect("Timeout waiting for availability");
+        assert!(matches!(resp, PlanetToExplorer::AvailableEnergyCellResponse { available_cells: 1 }));
 
-        let err = expl_rx.recv_timeout(Duration::from_millis(200));
-        assert!(err.is_err(), "Should refuse to use the last emergency cell");
+        expl_tx.send(ExplorerToPlanet::AvailableEnergyCellRequest { explorer_id: 98 }).unwrap();
+        let resp = other_rx.recv_timeout(Duration::from_secs(1)).expect("Timeout waiting for availability");
+        assert!(matches!(resp, PlanetToExplorer::AvailableEnergyCellResponse { available_cells: 0 }));
     }
 
     #[test]
@@ -1237,7 +2590,7 @@ mod tests {
         // 1. Manual Setup (We need the thread handle, which our helper doesn't return)
         let (orch_tx, orch_rx) = unbounded();
         let (planet_to_orch_tx, planet_to_orch_rx) = unbounded();
-        let (expl_tx, expl_rx) = unbounded();
+        let (_expl_tx, expl_rx) = unbounded();
 
         let mut planet = houston_we_have_a_borrow(
             orch_rx,
@@ -1278,4 +2631,4 @@ mod tests {
         // Trying to receive again should result in a Disconnect error.
         assert!(planet_to_orch_rx.recv().is_err(), "Channel should be disconnected after planet death");
     }
-}
\ No newline at end of file
+}
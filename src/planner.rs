@@ -0,0 +1,176 @@
+use common_game::logging::Payload;
+
+/// Weights of the [`RocketStrategy::Planner`](crate::RocketStrategy::Planner)
+/// cost/benefit model.
+///
+/// - `survival`: value of being protected against an asteroid.
+/// - `revenue`: value of a charged cell offered to explorers.
+///
+/// Only their ratio matters: the default values a rocket ten times as much
+/// as a sale.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PlannerWeights {
+    pub survival: f64,
+    pub revenue: f64,
+}
+
+impl Default for PlannerWeights {
+    fn default() -> Self {
+        PlannerWeights {
+            survival: 10.0,
+            revenue: 1.0,
+        }
+    }
+}
+
+/// What the planner can do with a charged cell when the planet has no rocket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PlannerOption {
+    /// Convert the cell into a rocket now.
+    BuildRocket,
+    /// Leave the cell charged and build on demand when an asteroid comes.
+    Wait,
+    /// Leave the cell to explorers, giving up the protection.
+    KeepEnergy,
+}
+
+/// Score of one option, split in its survival and revenue terms.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Score {
+    pub(crate) survival: f64,
+    pub(crate) revenue: f64,
+}
+
+impl Score {
+    pub(crate) fn total(&self) -> f64 {
+        self.survival + self.revenue
+    }
+}
+
+/// What the planner knows when it decides.
+///
+/// - `risk`: chance that the next event is an asteroid, 1 when one is hitting.
+/// - `demand`: chance that the next event is an explorer request.
+/// - `overflow`: every cell is charged, so a cell spent now is refilled by
+///   the sunray that would otherwise be lost.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Situation {
+    pub(crate) risk: f64,
+    pub(crate) demand: f64,
+    pub(crate) overflow: bool,
+}
+
+/// A scored decision: every option considered and the chosen one.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Decision {
+    pub(crate) situation: Situation,
+    /// `Wait` is not an option while an asteroid is hitting.
+    pub(crate) scores: Vec<(PlannerOption, Score)>,
+    pub(crate) choice: PlannerOption,
+}
+
+impl PlannerWeights {
+    /// Scores the options against keeping the energy, the baseline at 0:
+    ///
+    /// - `BuildRocket`: protected against the next asteroid, but the cell can
+    ///   no longer be sold (unless it would overflow).
+    /// - `Wait`: protected by an on-demand build unless the cell is sold
+    ///   first, and the cell is spent only if the asteroid comes.
+    ///
+    /// Ties go to the safest option.
+    pub(crate) fn decide(&self, situation: Situation) -> Decision {
+        let Situation { risk, demand, overflow } = situation;
+        let survival = self.survival * risk;
+        let revenue = self.revenue * demand;
+
+        let mut scores = vec![(
+            PlannerOption::BuildRocket,
+            Score {
+                survival,
+                revenue: if overflow { 0.0 } else { -revenue },
+            },
+        )];
+        if risk < 1.0 {
+            scores.push((
+                PlannerOption::Wait,
+                Score {
+                    survival: survival * (1.0 - demand),
+                    revenue: -revenue * risk,
+                },
+            ));
+        }
+        scores.push((PlannerOption::KeepEnergy, Score { survival: 0.0, revenue: 0.0 }));
+
+        let (choice, _) = scores[1..].iter().fold(scores[0], |best, &candidate| {
+            if candidate.1.total() > best.1.total() { candidate } else { best }
+        });
+        Decision { situation, scores, choice }
+    }
+}
+
+impl Decision {
+    /// Payload of the `PlannerDecision` event logged for every decision.
+    pub(crate) fn payload(&self, handler: &str) -> Payload {
+        let mut p = Payload::new();
+        p.insert("type".to_string(), "PlannerDecision".to_string());
+        p.insert("handler".to_string(), handler.to_string());
+        p.insert("risk".to_string(), format!("{:.3}", self.situation.risk));
+        p.insert("demand".to_string(), format!("{:.3}", self.situation.demand));
+        p.insert("overflow".to_string(), self.situation.overflow.to_string());
+        for (option, score) in &self.scores {
+            let key = match option {
+                PlannerOption::BuildRocket => "buildRocket",
+                PlannerOption::Wait => "wait",
+                PlannerOption::KeepEnergy => "keepEnergy",
+            };
+            p.insert(
+                key.to_string(),
+                format!(
+                    "{:.3} (survival {:.3}, revenue {:.3})",
+                    score.total(),
+                    score.survival,
+                    score.revenue
+                ),
+            );
+        }
+        p.insert("choice".to_string(), format!("{:?}", self.choice));
+        p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn situation(risk: f64, demand: f64) -> Situation {
+        Situation { risk, demand, overflow: false }
+    }
+
+    #[test]
+    fn test_planner_choices() {
+        let weights = PlannerWeights::default();
+        // Frequent asteroids: build now
+        assert_eq!(weights.decide(situation(0.5, 0.5)).choice, PlannerOption::BuildRocket);
+        // Rare asteroids and busy explorers: keep the cell charged, build on demand
+        assert_eq!(weights.decide(situation(0.05, 0.5)).choice, PlannerOption::Wait);
+        // Explorers worth far more than survival: sell
+        let greedy = PlannerWeights { survival: 1.0, revenue: 100.0 };
+        assert_eq!(greedy.decide(situation(0.05, 0.5)).choice, PlannerOption::KeepEnergy);
+        // An overflowing sunray makes the rocket free
+        let overflow = Situation { overflow: true, ..situation(0.05, 0.5) };
+        assert_eq!(weights.decide(overflow).choice, PlannerOption::BuildRocket);
+    }
+
+    #[test]
+    fn test_no_waiting_under_fire() {
+        let decision = PlannerWeights::default().decide(situation(1.0, 0.5));
+        assert!(decision.scores.iter().all(|(option, _)| *option != PlannerOption::Wait));
+        assert_eq!(decision.choice, PlannerOption::BuildRocket);
+
+        let payload = decision.payload("Asteroid");
+        assert_eq!(payload["buildRocket"], "9.500 (survival 10.000, revenue -0.500)");
+        assert_eq!(payload["keepEnergy"], "0.000 (survival 0.000, revenue 0.000)");
+        assert!(!payload.contains_key("wait"));
+    }
+}
@@ -0,0 +1,123 @@
+use crate::admission::Rejections;
+use crate::cells::PlanetCells;
+use crate::sink::LogSink;
+use crate::verbosity::LogVerbosity;
+use common_game::components::energy_cell::EnergyCell;
+use common_game::logging::{ActorType, Channel, EventType, LogEvent, Participant, Payload};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+/// Summary of a planet life, produced when it shuts down.
+///
+/// It is logged as an `InternalPlanetAction` event with `type` =
+/// `FinalReport`, and returned by `PlanetHandle::join`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FinalReport {
+    pub planet_id: u32,
+    pub sunrays: u64,
+    pub asteroids: u64,
+    /// Asteroids answered with a rocket.
+    pub asteroids_deflected: u64,
+    pub rockets_built: u64,
+    pub internal_state_requests: u64,
+    pub explorer_requests: u64,
+    pub resources_generated: u64,
+    /// Distinct explorers that received at least one response.
+    pub explorers_served: usize,
+    pub charged_cells: u32,
+    pub total_cells: u32,
+    pub has_rocket: bool,
+}
+
+impl FinalReport {
+    /// Every message the AI handled.
+    pub fn events_handled(&self) -> u64 {
+        self.sunrays + self.asteroids + self.internal_state_requests + self.explorer_requests
+    }
+
+    fn payload(&self) -> Payload {
+        let mut p = Payload::new();
+        p.insert("type".to_string(), "FinalReport".to_string());
+        p.insert("sunrays".to_string(), self.sunrays.to_string());
+        p.insert("asteroids".to_string(), self.asteroids.to_string());
+        p.insert("asteroidsDeflected".to_string(), self.asteroids_deflected.to_string());
+        p.insert("rocketsBuilt".to_string(), self.rockets_built.to_string());
+        p.insert("internalStateRequests".to_string(), self.internal_state_requests.to_string());
+        p.insert("explorerRequests".to_string(), self.explorer_requests.to_string());
+        p.insert("resourcesGenerated".to_string(), self.resources_generated.to_string());
+        p.insert("explorersServed".to_string(), self.explorers_served.to_string());
+        p.insert("energyCellCount".to_string(), self.charged_cells.to_string());
+        p.insert("totalCells".to_string(), self.total_cells.to_string());
+        p.insert("hasRocket".to_string(), self.has_rocket.to_string());
+        p
+    }
+}
+
+/// Whether the session counters, logged every time the AI is stopped, start
+/// from zero when the AI is started again.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SessionCounters {
+    /// Keep counting across stop/start: each stop logs the totals so far.
+    #[default]
+    Preserve,
+    /// Start every session from zero.
+    ResetOnStart,
+}
+
+/// Counters updated by the AI handlers, for the planet lifetime or a session.
+#[derive(Debug, Default)]
+pub(crate) struct PlanetStats {
+    pub(crate) sunrays: u64,
+    pub(crate) asteroids: u64,
+    pub(crate) asteroids_deflected: u64,
+    pub(crate) rockets_built: u64,
+    pub(crate) internal_state_requests: u64,
+    pub(crate) explorer_requests: u64,
+    pub(crate) resources_generated: u64,
+    pub(crate) explorers_served: HashSet<u32>,
+}
+
+/// Builds and logs the [`FinalReport`].
+///
+/// The runtime owns the AI as a `Box<dyn PlanetAI>`, so once `Planet::run`
+/// returns the AI cannot be reached anymore: the reporter shares the counters
+/// with it and is kept by the thread running the planet. It also shares the
+/// explorers the AI rejected, for the [`PlanetHandle`](crate::PlanetHandle).
+#[derive(Debug, Clone)]
+pub(crate) struct Reporter {
+    pub(crate) stats: Arc<Mutex<PlanetStats>>,
+    pub(crate) sink: Arc<dyn LogSink>,
+    pub(crate) verbosity: LogVerbosity,
+    pub(crate) rejections: Rejections,
+}
+
+impl Reporter {
+    pub(crate) fn finish<S: PlanetCells>(&self, state: &S) -> FinalReport {
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let report = FinalReport {
+            planet_id: state.id(),
+            sunrays: stats.sunrays,
+            asteroids: stats.asteroids,
+            asteroids_deflected: stats.asteroids_deflected,
+            rockets_built: stats.rockets_built,
+            internal_state_requests: stats.internal_state_requests,
+            explorer_requests: stats.explorer_requests,
+            resources_generated: stats.resources_generated,
+            explorers_served: stats.explorers_served.len(),
+            charged_cells: state.cells_iter().filter(|c| EnergyCell::is_charged(c)).count() as u32,
+            total_cells: state.cells_iter().count() as u32,
+            has_rocket: state.has_rocket(),
+        };
+        if self.verbosity.enabled(&EventType::InternalPlanetAction, &Channel::Info) {
+            self.sink.emit(&LogEvent::new(
+                Some(Participant::new(ActorType::Planet, report.planet_id)),
+                Some(Participant::new(ActorType::SelfActor, report.planet_id)),
+                EventType::InternalPlanetAction,
+                Channel::Info,
+                report.payload(),
+            ));
+        }
+        report
+    }
+}
@@ -0,0 +1,80 @@
+use common_game::utils::ID;
+use std::collections::HashMap;
+
+/// Controls how charged energy cells are held back for explorers between an
+/// `AvailableEnergyCellRequest` and the `GenerateResourceRequest` that follows it.
+///
+/// Time is measured in planet events: every message handled by the AI advances
+/// the clock by one tick.
+///
+/// - `ttl`: number of events after which an unused reservation is released.
+/// - `max_cells`: upper bound on the cells a single explorer can hold at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ReservationPolicy {
+    pub ttl: u64,
+    pub max_cells: u32,
+}
+
+impl Default for ReservationPolicy {
+    fn default() -> Self {
+        ReservationPolicy {
+            ttl: 10,
+            max_cells: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Reservation {
+    cells: u32,
+    expires_at: u64,
+}
+
+/// Book of the cells currently promised to explorers, keyed by explorer id.
+///
+/// Reservations are plain counters: they do not pin a specific cell index,
+/// they only guarantee that enough charged cells are kept aside.
+#[derive(Debug, Default)]
+pub(crate) struct Reservations {
+    held: HashMap<ID, Reservation>,
+}
+
+impl Reservations {
+    /// Drops every reservation whose expiry tick has been reached.
+    pub(crate) fn expire(&mut self, now: u64) {
+        self.held.retain(|_, r| r.expires_at > now);
+    }
+
+    pub(crate) fn held_by(&self, explorer_id: ID) -> u32 {
+        self.held.get(&explorer_id).map_or(0, |r| r.cells)
+    }
+
+    pub(crate) fn total(&self) -> u32 {
+        self.held.values().map(|r| r.cells).sum()
+    }
+
+    /// Replaces the reservation of `explorer_id`. A zero-sized reservation
+    /// removes the entry altogether.
+    pub(crate) fn reserve(&mut self, explorer_id: ID, cells: u32, expires_at: u64) {
+        if cells == 0 {
+            self.held.remove(&explorer_id);
+        } else {
+            self.held
+                .insert(explorer_id, Reservation { cells, expires_at });
+        }
+    }
+
+    /// Uses one reserved cell of `explorer_id`.
+    /// Returns `false` if the explorer was not holding any.
+    pub(crate) fn consume(&mut self, explorer_id: ID) -> bool {
+        let Some(r) = self.held.get_mut(&explorer_id) else {
+            return false;
+        };
+        r.cells -= 1;
+        if r.cells == 0 {
+            self.held.remove(&explorer_id);
+        }
+        true
+    }
+}
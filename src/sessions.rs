@@ -0,0 +1,90 @@
+use common_game::logging::Payload;
+use common_game::utils::ID;
+use std::collections::HashMap;
+
+/// What an explorer did on the planet since it landed.
+///
+/// A session is opened by `IncomingExplorerRequest`, if the
+/// [`ExplorerPolicy`](crate::ExplorerPolicy) accepts the explorer, and closed by
+/// `OutgoingExplorerRequest`, which logs it as an `ExplorerSessionSummary`
+/// event. Times are planet ticks, see [`ReservationPolicy`](crate::ReservationPolicy).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplorerSession {
+    pub arrived_at: u64,
+    pub requests: u64,
+    pub resources_received: u64,
+    pub cells_consumed: u64,
+}
+
+impl ExplorerSession {
+    pub(crate) fn summary(&self, explorer_id: ID, departed_at: u64) -> Payload {
+        let mut p = Payload::new();
+        p.insert("type".to_string(), "ExplorerSessionSummary".to_string());
+        p.insert("explorerId".to_string(), explorer_id.to_string());
+        p.insert("arrivedAt".to_string(), self.arrived_at.to_string());
+        p.insert("departedAt".to_string(), departed_at.to_string());
+        p.insert("requests".to_string(), self.requests.to_string());
+        p.insert("resourcesReceived".to_string(), self.resources_received.to_string());
+        p.insert("cellsConsumed".to_string(), self.cells_consumed.to_string());
+        p
+    }
+}
+
+/// Sessions of the explorers currently on the planet, keyed by explorer id.
+///
+/// Requests from an explorer that never landed are not tracked.
+#[derive(Debug, Default)]
+pub(crate) struct ExplorerSessions {
+    open: HashMap<ID, ExplorerSession>,
+}
+
+impl ExplorerSessions {
+    /// Opens a session for `explorer_id`, replacing any session it had.
+    pub(crate) fn open(&mut self, explorer_id: ID, now: u64) {
+        let session = ExplorerSession {
+            arrived_at: now,
+            requests: 0,
+            resources_received: 0,
+            cells_consumed: 0,
+        };
+        self.open.insert(explorer_id, session);
+    }
+
+    pub(crate) fn close(&mut self, explorer_id: ID) -> Option<ExplorerSession> {
+        self.open.remove(&explorer_id)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.open.len()
+    }
+
+    pub(crate) fn get(&self, explorer_id: ID) -> Option<&ExplorerSession> {
+        self.open.get(&explorer_id)
+    }
+
+    /// Applies `update` to the session of `explorer_id`, if it has one.
+    pub(crate) fn record(&mut self, explorer_id: ID, update: impl FnOnce(&mut ExplorerSession)) {
+        if let Some(session) = self.open.get_mut(&explorer_id) {
+            update(session);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_landed_explorers_are_tracked() {
+        let mut sessions = ExplorerSessions::default();
+        sessions.open(7, 3);
+        sessions.record(7, |s| s.requests += 1);
+        sessions.record(9, |s| s.requests += 1);
+        assert!(sessions.get(9).is_none());
+
+        let session = sessions.close(7).unwrap();
+        assert_eq!(session.requests, 1);
+        assert_eq!(session.summary(7, 10)["arrivedAt"], "3");
+        assert!(sessions.close(7).is_none());
+    }
+}
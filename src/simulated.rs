@@ -0,0 +1,214 @@
+use crate::cells::PlanetCells;
+use crate::{build_planet, FinalReport, GeneratedBatch, PlanetConfig, PlanetCoreThinkingModel};
+use common_game::components::energy_cell::EnergyCell;
+use common_game::components::planet::{DummyPlanetState, Planet, PlanetAI, PlanetState, PlanetType};
+use common_game::components::rocket::Rocket;
+use common_game::components::resource::{BasicResourceType, Combinator, Generator};
+use common_game::components::sunray::Sunray;
+use common_game::protocols::planet_explorer::{ExplorerToPlanet, PlanetToExplorer};
+use crossbeam_channel::unbounded;
+use std::slice::Iter;
+
+/// Stand-in for the rocket of a [`SimulatedPlanetState`]; the real `Rocket`
+/// can only be built by `common_game`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct SimulatedRocket;
+
+/// A planet state owned by the caller, following the same rules as
+/// `PlanetState` for the given [`PlanetType`].
+///
+/// It also counts the rockets built on it, which the runtime state does not.
+#[derive(Debug)]
+pub struct SimulatedPlanetState {
+    id: u32,
+    cells: Vec<EnergyCell>,
+    rocket: Option<SimulatedRocket>,
+    can_have_rocket: bool,
+    rockets_built: u32,
+}
+
+impl SimulatedPlanetState {
+    pub fn new(id: u32, planet_type: PlanetType) -> Self {
+        // Mirrors `PlanetType::constraints`, whose fields are private.
+        let (n_cells, can_have_rocket) = match planet_type {
+            PlanetType::A => (5, true),
+            PlanetType::B => (1, false),
+            PlanetType::C => (1, true),
+            PlanetType::D => (5, false),
+        };
+        SimulatedPlanetState {
+            id,
+            cells: (0..n_cells).map(|_| EnergyCell::new()).collect(),
+            rocket: None,
+            can_have_rocket,
+            rockets_built: 0,
+        }
+    }
+
+    pub fn charged_count(&self) -> u32 {
+        self.cells.iter().filter(|c| c.is_charged()).count() as u32
+    }
+
+    pub fn rockets_built(&self) -> u32 {
+        self.rockets_built
+    }
+}
+
+impl PlanetCells for SimulatedPlanetState {
+    type Rocket = SimulatedRocket;
+
+    fn id(&self) -> u32 {
+        self.id
+    }
+    fn cells_iter(&self) -> Iter<'_, EnergyCell> {
+        self.cells.iter()
+    }
+    fn cell_mut(&mut self, i: usize) -> &mut EnergyCell {
+        &mut self.cells[i]
+    }
+    fn can_have_rocket(&self) -> bool {
+        self.can_have_rocket
+    }
+    fn has_rocket(&self) -> bool {
+        self.rocket.is_some()
+    }
+    fn take_rocket(&mut self) -> Option<SimulatedRocket> {
+        self.rocket.take()
+    }
+    fn build_rocket(&mut self, i: usize) -> Result<(), String> {
+        if !self.can_have_rocket {
+            return Err("This planet type can't have rockets.".to_string());
+        }
+        if self.has_rocket() {
+            return Err("This planet already has a rocket.".to_string());
+        }
+        self.cells[i].discharge()?;
+        self.rocket = Some(SimulatedRocket);
+        self.rockets_built += 1;
+        Ok(())
+    }
+}
+
+/// A planet AI wired to a [`SimulatedPlanetState`], driven by direct calls
+/// instead of channels and a planet thread.
+///
+/// Every method maps to one orchestrator or explorer message and returns what
+/// the planet would have sent back, so a test reads as a sequence of calls.
+pub struct SimulatedPlanet {
+    pub ai: PlanetCoreThinkingModel,
+    pub state: SimulatedPlanetState,
+    // The generator and combinator can only be built by `Planet::new`,
+    // so they are borrowed from a planet that is never run.
+    template: Planet,
+}
+
+/// The AI of the template planet, which is never run.
+struct RulesOnly;
+
+impl PlanetAI for RulesOnly {
+    fn handle_sunray(&mut self, _: &mut PlanetState, _: &Generator, _: &Combinator, _: Sunray) {}
+
+    fn handle_asteroid(&mut self, _: &mut PlanetState, _: &Generator, _: &Combinator) -> Option<Rocket> {
+        None
+    }
+
+    fn handle_internal_state_req(&mut self, state: &mut PlanetState, _: &Generator, _: &Combinator) -> DummyPlanetState {
+        state.to_dummy()
+    }
+
+    fn handle_explorer_msg(
+        &mut self,
+        _: &mut PlanetState,
+        _: &Generator,
+        _: &Combinator,
+        _: ExplorerToPlanet,
+    ) -> Option<PlanetToExplorer> {
+        None
+    }
+}
+
+impl SimulatedPlanet {
+    /// Builds the simulated counterpart of `create_planet(config, ..)`.
+    ///
+    /// # Errors
+    /// Same as [`create_planet`](crate::create_planet).
+    pub fn new(config: PlanetConfig) -> Result<Self, String> {
+        let ai = PlanetCoreThinkingModel::new(&config);
+        ai.log_creation(&config);
+        let state = SimulatedPlanetState::new(config.planet_id, config.planet_type);
+        let (_, rx_orchestrator) = unbounded();
+        let (tx_orchestrator, _) = unbounded();
+        let (_, rx_explorer) = unbounded();
+        let template = build_planet(&config, Box::new(RulesOnly), rx_orchestrator, tx_orchestrator, rx_explorer)?;
+        Ok(SimulatedPlanet { ai, state, template })
+    }
+
+    pub fn generator(&self) -> &Generator {
+        self.template.generator()
+    }
+
+    pub fn combinator(&self) -> &Combinator {
+        self.template.combinator()
+    }
+
+    pub fn sunray(&mut self) {
+        let (generator, combinator) = (self.template.generator(), self.template.combinator());
+        self.ai
+            .handle_sunray(&mut self.state, generator, combinator, Sunray::default());
+    }
+
+    pub fn asteroid(&mut self) -> Option<SimulatedRocket> {
+        let (generator, combinator) = (self.template.generator(), self.template.combinator());
+        self.ai.handle_asteroid(&mut self.state, generator, combinator)
+    }
+
+    pub fn internal_state(&mut self) -> DummyPlanetState {
+        let (generator, combinator) = (self.template.generator(), self.template.combinator());
+        self.ai
+            .handle_internal_state_req(&mut self.state, generator, combinator)
+    }
+
+    pub fn explorer_msg(&mut self, msg: ExplorerToPlanet) -> Option<PlanetToExplorer> {
+        let (generator, combinator) = (self.template.generator(), self.template.combinator());
+        self.ai
+            .handle_explorer_msg(&mut self.state, generator, combinator, msg)
+    }
+
+    /// See [`PlanetCoreThinkingModel::generate_batch`].
+    pub fn generate_batch(&mut self, explorer_id: u32, resource: BasicResourceType, quantity: u32) -> GeneratedBatch {
+        let generator = self.template.generator();
+        self.ai
+            .generate_batch(&mut self.state, generator, explorer_id, resource, quantity)
+    }
+
+    /// Same as the runtime receiving `IncomingExplorerRequest`.
+    pub fn explorer_arrival(&mut self, explorer_id: u32) {
+        let (generator, combinator) = (self.template.generator(), self.template.combinator());
+        self.ai
+            .on_explorer_arrival(&mut self.state, generator, combinator, explorer_id);
+    }
+
+    /// Same as the runtime receiving `OutgoingExplorerRequest`.
+    pub fn explorer_departure(&mut self, explorer_id: u32) {
+        let (generator, combinator) = (self.template.generator(), self.template.combinator());
+        self.ai
+            .on_explorer_departure(&mut self.state, generator, combinator, explorer_id);
+    }
+
+    /// Same as the runtime receiving `StartPlanetAI` while stopped.
+    pub fn start(&mut self) {
+        let (generator, combinator) = (self.template.generator(), self.template.combinator());
+        self.ai.on_start(&self.state, generator, combinator);
+    }
+
+    /// Same as the runtime receiving `StopPlanetAI` while running.
+    pub fn stop(&mut self) {
+        let (generator, combinator) = (self.template.generator(), self.template.combinator());
+        self.ai.on_stop(&self.state, generator, combinator);
+    }
+
+    /// The report the planet would produce if it shut down now.
+    pub fn final_report(&self) -> FinalReport {
+        self.ai.final_report(&self.state)
+    }
+}
@@ -0,0 +1,375 @@
+use common_game::logging::{ActorType, Channel, EventType, LogEvent, Participant};
+use std::collections::VecDeque;
+use std::fmt::{Debug, Formatter};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Destination of the [`LogEvent`]s emitted by a planet.
+///
+/// A sink is chosen per planet through `PlanetConfig::log_sink`; planets built
+/// without one use [`GlobalLogger`]. Sinks are shared (`Arc`) and called from
+/// the planet thread, so implementations use interior mutability.
+pub trait LogSink: Send + Sync + Debug {
+    fn emit(&self, event: &LogEvent);
+}
+
+/// Forwards events to the `log` facade (`LogEvent::emit`), i.e. to whatever
+/// global logger is installed, such as `env_logger`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GlobalLogger;
+
+impl LogSink for GlobalLogger {
+    fn emit(&self, event: &LogEvent) {
+        event.emit();
+    }
+}
+
+/// Prints every event on stdout, one per line, using its `Display` format.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdoutSink;
+
+impl LogSink for StdoutSink {
+    fn emit(&self, event: &LogEvent) {
+        println!("{event}");
+    }
+}
+
+/// Keeps the last `capacity` events in memory. Clones share the same buffer,
+/// so a test can keep one and hand the other to the planet.
+#[derive(Debug, Clone)]
+pub struct RingBufferSink {
+    capacity: usize,
+    events: Arc<Mutex<VecDeque<LogEvent>>>,
+}
+
+impl RingBufferSink {
+    pub fn new(capacity: usize) -> Self {
+        RingBufferSink {
+            capacity,
+            events: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Returns a copy of the buffered events, oldest first.
+    pub fn events(&self) -> Vec<LogEvent> {
+        self.lock().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<LogEvent>> {
+        // A panic while holding the lock cannot leave the deque inconsistent.
+        self.events.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl LogSink for RingBufferSink {
+    fn emit(&self, event: &LogEvent) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut events = self.lock();
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(event.clone());
+    }
+}
+
+/// Collects the events emitted by one planet (or by every planet, see
+/// [`CaptureSink::all`]) so tests can assert on what was logged.
+///
+/// An event belongs to a planet when the planet is its sender or receiver.
+/// Clones share the same store, like [`RingBufferSink`].
+#[derive(Debug, Clone)]
+pub struct CaptureSink {
+    planet_id: Option<u32>,
+    events: Arc<Mutex<Vec<LogEvent>>>,
+}
+
+impl CaptureSink {
+    pub fn for_planet(planet_id: u32) -> Self {
+        CaptureSink {
+            planet_id: Some(planet_id),
+            events: Arc::default(),
+        }
+    }
+
+    pub fn all() -> Self {
+        CaptureSink {
+            planet_id: None,
+            events: Arc::default(),
+        }
+    }
+
+    /// Returns a copy of the captured events, in emission order.
+    pub fn events(&self) -> Vec<LogEvent> {
+        self.lock().clone()
+    }
+
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    pub fn by_event_type(&self, event_type: &EventType) -> Vec<LogEvent> {
+        self.filter(|e| e.event_type == *event_type)
+    }
+
+    pub fn by_channel(&self, channel: &Channel) -> Vec<LogEvent> {
+        self.filter(|e| e.channel == *channel)
+    }
+
+    /// Events whose payload contains `key`, whatever its value.
+    pub fn with_payload_key(&self, key: &str) -> Vec<LogEvent> {
+        self.filter(|e| e.payload.contains_key(key))
+    }
+
+    /// Events whose payload maps `key` to `value`.
+    pub fn with_payload(&self, key: &str, value: &str) -> Vec<LogEvent> {
+        self.filter(|e| e.payload.get(key).is_some_and(|v| v == value))
+    }
+
+    /// Events matching an arbitrary predicate, for queries the helpers above
+    /// do not cover.
+    pub fn filter(&self, predicate: impl Fn(&LogEvent) -> bool) -> Vec<LogEvent> {
+        self.lock().iter().filter(|e| predicate(e)).cloned().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<LogEvent>> {
+        self.events.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn involves_planet(event: &LogEvent, planet_id: u32) -> bool {
+        [&event.sender, &event.receiver].into_iter().flatten().any(|p| {
+            p.id == planet_id && matches!(p.actor_type, ActorType::Planet | ActorType::SelfActor)
+        })
+    }
+}
+
+impl LogSink for CaptureSink {
+    fn emit(&self, event: &LogEvent) {
+        if let Some(id) = self.planet_id
+            && !Self::involves_planet(event, id)
+        {
+            return;
+        }
+        self.lock().push(event.clone());
+    }
+}
+
+/// Hands every event to a user function.
+pub struct CallbackSink {
+    callback: Box<dyn Fn(&LogEvent) + Send + Sync>,
+}
+
+impl CallbackSink {
+    pub fn new(callback: impl Fn(&LogEvent) + Send + Sync + 'static) -> Self {
+        CallbackSink {
+            callback: Box::new(callback),
+        }
+    }
+}
+
+impl Debug for CallbackSink {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackSink").finish_non_exhaustive()
+    }
+}
+
+impl LogSink for CallbackSink {
+    fn emit(&self, event: &LogEvent) {
+        (self.callback)(event);
+    }
+}
+
+/// Appends events as JSON lines to a file, rotating it when it grows past
+/// `max_bytes`: `path` becomes `path.1`, `path.1` becomes `path.2`, and so on,
+/// keeping at most `keep` old files.
+///
+/// Write errors are dropped: logging must never bring a planet down.
+#[derive(Debug)]
+pub struct JsonLinesFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: Mutex<(File, u64)>,
+}
+
+impl JsonLinesFile {
+    /// Opens (or creates) `path` in append mode.
+    ///
+    /// # Errors
+    /// Returns the I/O error if the file cannot be opened.
+    pub fn new(path: impl AsRef<Path>, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(JsonLinesFile {
+            path,
+            max_bytes,
+            keep,
+            file: Mutex::new((file, written)),
+        })
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&self) -> io::Result<File> {
+        if self.keep == 0 {
+            return File::create(&self.path);
+        }
+        for n in (1..self.keep).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                std::fs::rename(from, self.rotated(n + 1))?;
+            }
+        }
+        std::fs::rename(&self.path, self.rotated(1))?;
+        File::create(&self.path)
+    }
+}
+
+impl LogSink for JsonLinesFile {
+    fn emit(&self, event: &LogEvent) {
+        let mut line = to_json(event);
+        line.push('\n');
+        let mut guard = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.1 > 0 && guard.1 + line.len() as u64 > self.max_bytes {
+            match self.rotate() {
+                Ok(file) => *guard = (file, 0),
+                Err(_) => return,
+            }
+        }
+        if guard.0.write_all(line.as_bytes()).is_ok() {
+            guard.1 += line.len() as u64;
+        }
+    }
+}
+
+/// Renders an event as a single-line JSON object.
+pub(crate) fn to_json(event: &LogEvent) -> String {
+    let participant = |p: &Option<Participant>| match p {
+        Some(p) => format!(
+            "{{\"actor_type\":{},\"id\":{}}}",
+            json_string(&format!("{:?}", p.actor_type)),
+            p.id
+        ),
+        None => "null".to_string(),
+    };
+    let payload: Vec<String> = event
+        .payload
+        .iter()
+        .map(|(k, v)| format!("{}:{}", json_string(k), json_string(v)))
+        .collect();
+    format!(
+        "{{\"timestamp_unix\":{},\"sender\":{},\"receiver\":{},\"event_type\":{},\"channel\":{},\"payload\":{{{}}}}}",
+        event.timestamp_unix,
+        participant(&event.sender),
+        participant(&event.receiver),
+        json_string(&format!("{:?}", event.event_type)),
+        json_string(&format!("{:?}", event.channel)),
+        payload.join(",")
+    )
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common_game::logging::Payload;
+
+    fn sample_event(n: usize) -> LogEvent {
+        let mut p = Payload::new();
+        p.insert("n".to_string(), n.to_string());
+        p.insert("quote".to_string(), "say \"hi\"\n".to_string());
+        LogEvent::new(
+            Some(Participant::new(ActorType::Planet, 1u32)),
+            None,
+            EventType::InternalPlanetAction,
+            Channel::Info,
+            p,
+        )
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_last_events() {
+        let sink = RingBufferSink::new(2);
+        let shared = sink.clone();
+        for n in 0..3 {
+            sink.emit(&sample_event(n));
+        }
+        let kept: Vec<_> = shared.events().iter().map(|e| e.payload["n"].clone()).collect();
+        assert_eq!(kept, vec!["1", "2"]);
+    }
+
+    #[test]
+    fn test_capture_keeps_only_its_planet() {
+        let sink = CaptureSink::for_planet(1);
+        sink.emit(&sample_event(0));
+        let mut other = sample_event(1);
+        other.sender = Some(Participant::new(ActorType::Planet, 2u32));
+        sink.emit(&other);
+
+        assert_eq!(sink.events().len(), 1);
+        assert_eq!(sink.with_payload("n", "0").len(), 1);
+        assert!(sink.with_payload("n", "1").is_empty());
+        assert_eq!(sink.by_channel(&Channel::Info).len(), 1);
+    }
+
+    #[test]
+    fn test_json_line_is_escaped() {
+        let mut event = sample_event(7);
+        event.timestamp_unix = 42;
+        assert_eq!(
+            to_json(&event),
+            "{\"timestamp_unix\":42,\"sender\":{\"actor_type\":\"Planet\",\"id\":1},\"receiver\":null,\
+             \"event_type\":\"InternalPlanetAction\",\"channel\":\"Info\",\
+             \"payload\":{\"n\":\"7\",\"quote\":\"say \\\"hi\\\"\\n\"}}"
+        );
+    }
+
+    #[test]
+    fn test_json_file_rotates() {
+        let dir = std::env::temp_dir().join(format!("planet-sink-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.jsonl");
+        let line_len = to_json(&sample_event(0)).len() as u64 + 1;
+
+        let sink = JsonLinesFile::new(&path, line_len * 2, 1).unwrap();
+        for n in 0..5 {
+            sink.emit(&sample_event(n));
+        }
+
+        let current = std::fs::read_to_string(&path).unwrap();
+        let previous = std::fs::read_to_string(dir.join("events.jsonl.1")).unwrap();
+        assert_eq!(current.lines().count(), 1);
+        assert_eq!(previous.lines().count(), 2);
+        assert!(!dir.join("events.jsonl.2").exists(), "Only one old file should be kept");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use std::collections::HashSet;
+
+/// Explorer service levels, chosen from the share of charged energy cells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ServiceTier {
+    /// Every explorer is served.
+    Full,
+    /// Only the explorers in [`ServiceTiers::priority_explorers`] are served.
+    PriorityOnly,
+    /// The energy is kept for rockets: no explorer is served.
+    RocketOnly,
+}
+
+/// Enables energy-aware service tiers for explorer generation requests.
+///
+/// The tier depends on the percentage of charged cells: at least
+/// `full_service_percent` gives [`ServiceTier::Full`], at least
+/// `priority_service_percent` gives [`ServiceTier::PriorityOnly`], anything
+/// below is [`ServiceTier::RocketOnly`].
+///
+/// Cells already reserved by an explorer are still handed to it whatever the
+/// tier. The protocol has no room for the tier in `AvailableEnergyCellResponse`,
+/// so an explorer that would be refused is told no cell is available; the tier
+/// itself is in the `serviceTier` key of the logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ServiceTiers {
+    pub full_service_percent: u32,
+    pub priority_service_percent: u32,
+    pub priority_explorers: HashSet<u32>,
+}
+
+impl ServiceTiers {
+    /// Tiers with the default thresholds (60% and 40%, i.e. 3 and 2 cells of
+    /// a type A planet) and the given priority explorers.
+    pub fn new(priority_explorers: impl IntoIterator<Item = u32>) -> Self {
+        ServiceTiers {
+            priority_explorers: priority_explorers.into_iter().collect(),
+            ..ServiceTiers::default()
+        }
+    }
+
+    pub fn tier(&self, charged: u32, total: u32) -> ServiceTier {
+        let percent = (charged * 100).checked_div(total).unwrap_or(0);
+        if percent >= self.full_service_percent {
+            ServiceTier::Full
+        } else if percent >= self.priority_service_percent {
+            ServiceTier::PriorityOnly
+        } else {
+            ServiceTier::RocketOnly
+        }
+    }
+
+    pub fn serves(&self, tier: ServiceTier, explorer_id: u32) -> bool {
+        match tier {
+            ServiceTier::Full => true,
+            ServiceTier::PriorityOnly => self.priority_explorers.contains(&explorer_id),
+            ServiceTier::RocketOnly => false,
+        }
+    }
+}
+
+impl Default for ServiceTiers {
+    fn default() -> Self {
+        ServiceTiers {
+            full_service_percent: 60,
+            priority_service_percent: 40,
+            priority_explorers: HashSet::new(),
+        }
+    }
+}
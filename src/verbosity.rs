@@ -0,0 +1,101 @@
+use common_game::logging::{Channel, EventType};
+
+/// Which events a planet logs, by channel and event type.
+///
+/// Channels are ordered from `Error` (least verbose) to `Trace` (most verbose);
+/// an event is logged when its channel is not more verbose than the limit set
+/// for its event type, or the default limit if the type has no override.
+///
+/// ```
+/// use common_game::logging::{Channel, EventType};
+/// use Planet::LogVerbosity;
+///
+/// // Keep explorer failures, drop the sunray and state chatter.
+/// let verbosity = LogVerbosity::new(Channel::Info)
+///     .with_event(EventType::MessagePlanetToExplorer, Channel::Warning);
+/// assert!(verbosity.enabled(&EventType::MessagePlanetToExplorer, &Channel::Warning));
+/// assert!(!verbosity.enabled(&EventType::MessagePlanetToOrchestrator, &Channel::Debug));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogVerbosity {
+    max_channel: Option<Channel>,
+    per_event: Vec<(EventType, Option<Channel>)>,
+    eager_payloads: bool,
+}
+
+impl LogVerbosity {
+    /// Logs every event up to `max_channel`.
+    pub fn new(max_channel: Channel) -> Self {
+        LogVerbosity {
+            max_channel: Some(max_channel),
+            per_event: Vec::new(),
+            eager_payloads: false,
+        }
+    }
+
+    /// Logs nothing, unless an event type is enabled with [`with_event`](Self::with_event).
+    pub fn off() -> Self {
+        LogVerbosity {
+            max_channel: None,
+            per_event: Vec::new(),
+            eager_payloads: false,
+        }
+    }
+
+    /// Overrides the limit for one event type.
+    pub fn with_event(self, event_type: EventType, max_channel: Channel) -> Self {
+        self.set_event(event_type, Some(max_channel))
+    }
+
+    /// Silences one event type.
+    pub fn without_event(self, event_type: EventType) -> Self {
+        self.set_event(event_type, None)
+    }
+
+    /// Builds the payload of every event before checking the filter, as the
+    /// planet did before payloads were built lazily. Only meant as the
+    /// baseline of the `log_overhead` benchmark.
+    #[doc(hidden)]
+    pub fn with_eager_payloads(mut self) -> Self {
+        self.eager_payloads = true;
+        self
+    }
+
+    pub(crate) fn eager_payloads(&self) -> bool {
+        self.eager_payloads
+    }
+
+    fn set_event(mut self, event_type: EventType, max_channel: Option<Channel>) -> Self {
+        self.per_event.retain(|(e, _)| *e != event_type);
+        self.per_event.push((event_type, max_channel));
+        self
+    }
+
+    pub fn enabled(&self, event_type: &EventType, channel: &Channel) -> bool {
+        let max_channel = self
+            .per_event
+            .iter()
+            .find(|(e, _)| e == event_type)
+            .map_or(&self.max_channel, |(_, max)| max);
+        max_channel
+            .as_ref()
+            .is_some_and(|max| rank(channel) <= rank(max))
+    }
+}
+
+impl Default for LogVerbosity {
+    /// Logs everything.
+    fn default() -> Self {
+        LogVerbosity::new(Channel::Trace)
+    }
+}
+
+fn rank(channel: &Channel) -> u8 {
+    match channel {
+        Channel::Error => 0,
+        Channel::Warning => 1,
+        Channel::Info => 2,
+        Channel::Debug => 3,
+        Channel::Trace => 4,
+    }
+}